@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::*};
+use serde::Deserialize;
+
+use super::ron_asset::RonAssetPlugin;
+
+const ARCHETYPES_PATH: &str = "asteroids.archetypes.ron";
+
+// 被摧毁时的特殊行为
+//  - Inert:    没有特殊行为
+//  - Volatile: 爆炸，对 radius 范围内所有有生命值的实体造成 damage 点伤害
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum AsteroidBehavior {
+    #[default]
+    Inert,
+    Volatile { radius: f32, damage: f32 },
+}
+
+// 一种小行星的定义
+//  - weight:      波次没有指定权重表时，随机选择的权重
+//  - health/mass: 在大小等级的生命值和质量上乘的系数
+//  - speed_range: 在波次速度上乘的随机系数范围
+//  - tint:        程序生成网格的颜色 (r, g, b)，glTF 模型不受影响
+//  - score:       摧毁一颗（任意大小）得到的分数
+#[derive(Debug, Clone, Deserialize)]
+pub struct AsteroidArchetype {
+    pub name: String,
+    pub weight: f32,
+    pub health: f32,
+    pub mass: f32,
+    pub speed_range: (f32, f32),
+    pub tint: (f32, f32, f32),
+    pub score: u32,
+    #[serde(default)]
+    pub behavior: AsteroidBehavior,
+}
+
+impl AsteroidArchetype {
+    pub fn color(&self) -> Color {
+        Color::rgb(self.tint.0, self.tint.1, self.tint.2)
+    }
+
+    pub fn random_speed_scale(&self, rng: &mut impl Rng) -> f32 {
+        let (min, max) = self.speed_range;
+        if min < max { rng.gen_range(min..max) } else { min }
+    }
+}
+
+// 从 assets/asteroids.archetypes.ron 加载的小行星种类列表
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct AsteroidArchetypes {
+    pub archetypes: Vec<AsteroidArchetype>,
+}
+
+// 当前使用的种类表。配置文件加载（或热重载）完成后复制到这里，
+// 生成小行星的系统只需要读取这个资源；加载完成之前只有默认的 rocky 一种
+#[derive(Resource, Debug)]
+pub struct ArchetypeTable {
+    archetypes: Vec<AsteroidArchetype>,
+    handle: Handle<AsteroidArchetypes>,
+}
+
+impl Default for ArchetypeTable {
+    fn default() -> Self {
+        Self {
+            archetypes: vec![AsteroidArchetype {
+                name: "rocky".to_string(),
+                weight: 1.0,
+                health: 1.0,
+                mass: 1.0,
+                speed_range: (1.0, 1.0),
+                tint: (0.45, 0.4, 0.36),
+                score: 100,
+                behavior: AsteroidBehavior::Inert,
+            }],
+            handle: Handle::default(),
+        }
+    }
+}
+
+impl ArchetypeTable {
+    pub fn iter(&self) -> impl Iterator<Item = &AsteroidArchetype> {
+        self.archetypes.iter()
+    }
+
+    // 种类表重新加载后索引可能越界，越界时使用第一种
+    pub fn get(&self, index: usize) -> &AsteroidArchetype {
+        self.archetypes.get(index).unwrap_or(&self.archetypes[0])
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.archetypes.iter().position(|archetype| archetype.name == name)
+    }
+
+    // 按权重表 (名称, 权重) 选择一种，权重表为空或无效时按各种类自己的 weight 选择
+    pub fn choose(&self, weights: &[(String, f32)], rng: &mut impl Rng) -> usize {
+        let table: Vec<(usize, f32)> = weights
+            .iter()
+            .filter_map(|(name, weight)| Some((self.index_of(name)?, *weight)))
+            .collect();
+        if let Ok(distribution) = WeightedIndex::new(table.iter().map(|(_, weight)| weight.max(0.0))) {
+            return table[distribution.sample(rng)].0;
+        }
+        WeightedIndex::new(self.archetypes.iter().map(|archetype| archetype.weight.max(0.0)))
+            .map_or(0, |distribution| distribution.sample(rng))
+    }
+}
+
+pub struct ArchetypePlugin;
+
+impl Plugin for ArchetypePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<AsteroidArchetypes>::new(&["archetypes.ron"]))
+            .init_resource::<ArchetypeTable>()
+            .add_systems(Startup, load_archetypes)
+            .add_systems(Update, sync_archetype_table);
+    }
+}
+
+fn load_archetypes(mut table: ResMut<ArchetypeTable>, asset_server: Res<AssetServer>) {
+    table.handle = asset_server.load(ARCHETYPES_PATH);
+}
+
+fn sync_archetype_table(
+    mut table: ResMut<ArchetypeTable>,
+    mut asset_events: EventReader<AssetEvent<AsteroidArchetypes>>,
+    assets: Res<Assets<AsteroidArchetypes>>,
+) {
+    for event in asset_events.read() {
+        if !event.is_loaded_with_dependencies(&table.handle) && !event.is_modified(&table.handle) {
+            continue;
+        }
+        match assets.get(&table.handle) {
+            Some(loaded) if !loaded.archetypes.is_empty() => {
+                table.archetypes = loaded.archetypes.clone();
+                info!("Loaded {} asteroid archetypes", table.archetypes.len());
+            }
+            _ => warn!("{ARCHETYPES_PATH} has no archetypes, keeping the current table"),
+        }
+    }
+}
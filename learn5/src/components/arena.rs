@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+use super::{movement::Velocity, schedule::PhysicsSet};
+
+// 默认的游戏区域半宽（X）和半高（Z），与摄像机高度 80 时大致可见的范围一致
+const ARENA_HALF_EXTENTS: Vec2 = Vec2::new(60.0, 35.0);
+
+// 世界模式
+//  - Wrap: 环形世界，带有 ScreenWrap 的实体从一侧离开后会从对侧出现
+//  - Open: 开放世界，离开区域的实体按原来的距离规则销毁，带有 ClampToArena 的实体停在区域边缘
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArenaMode {
+    #[default]
+    Wrap,
+    Open,
+}
+
+// Arena 资源描述了以原点为中心、位于 XZ 平面的矩形游戏区域
+#[derive(Resource, Debug)]
+pub struct Arena {
+    pub mode: ArenaMode,
+    pub half_extents: Vec2,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            mode: ArenaMode::default(),
+            half_extents: ARENA_HALF_EXTENTS,
+        }
+    }
+}
+
+impl Arena {
+    pub fn is_wrapping(&self) -> bool {
+        self.mode == ArenaMode::Wrap
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.half_extents * 2.0
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.x.abs() <= self.half_extents.x && position.z.abs() <= self.half_extents.y
+    }
+
+    // 把位置限制在区域内，Y 坐标保持不变
+    pub fn clamp_position(&self, position: Vec3) -> Vec3 {
+        Vec3::new(
+            position.x.clamp(-self.half_extents.x, self.half_extents.x),
+            position.y,
+            position.z.clamp(-self.half_extents.y, self.half_extents.y),
+        )
+    }
+
+    // 把位置折回到区域内，Y 坐标保持不变
+    pub fn wrap_position(&self, position: Vec3) -> Vec3 {
+        let size = self.size();
+        Vec3::new(
+            wrap(position.x, self.half_extents.x, size.x),
+            position.y,
+            wrap(position.z, self.half_extents.y, size.y),
+        )
+    }
+
+    // 从 from 指向 to 的位移。
+    // 环形世界中取最短的那一条，这样靠近接缝两侧的实体也能正确地检测到彼此
+    pub fn offset(&self, from: Vec3, to: Vec3) -> Vec3 {
+        let delta = to - from;
+        if !self.is_wrapping() {
+            return delta;
+        }
+        let size = self.size();
+        Vec3::new(
+            delta.x - size.x * (delta.x / size.x).round(),
+            delta.y,
+            delta.z - size.y * (delta.z / size.y).round(),
+        )
+    }
+}
+
+fn wrap(value: f32, half_extent: f32, size: f32) -> f32 {
+    (value + half_extent).rem_euclid(size) - half_extent
+}
+
+// 标记组件：环形世界中离开区域后从对侧出现
+#[derive(Component, Debug)]
+pub struct ScreenWrap;
+
+// 标记组件：离开区域后立即销毁（无论世界模式）
+#[derive(Component, Debug)]
+pub struct DespawnOutsideArena;
+
+// 标记组件：开放世界中不能离开区域，到达边缘时停下，也不会因为距离太远被销毁。
+// 环形世界中不起作用，需要环绕的实体同时带上 ScreenWrap
+#[derive(Component, Debug)]
+pub struct ClampToArena;
+
+pub struct ArenaPlugin;
+
+// 环绕和限制在区域内都发生在位置积分之后、碰撞检测之前
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Arena>()
+            .add_systems(Update, arena_mode_controls)
+            .add_systems(FixedUpdate, (wrap_entities, clamp_entities).in_set(PhysicsSet::WrapPositions));
+    }
+}
+
+// M 在环形世界和开放世界之间切换
+fn arena_mode_controls(mut arena: ResMut<Arena>, keyboard_input: Res<Input<KeyCode>>) {
+    if keyboard_input.just_pressed(KeyCode::M) {
+        arena.mode = match arena.mode {
+            ArenaMode::Wrap => ArenaMode::Open,
+            ArenaMode::Open => ArenaMode::Wrap,
+        };
+        info!("Arena mode: {:?}", arena.mode);
+    }
+}
+
+fn wrap_entities(mut query: Query<&mut Transform, With<ScreenWrap>>, arena: Res<Arena>) {
+    if !arena.is_wrapping() {
+        return;
+    }
+    for mut transform in query.iter_mut() {
+        if !arena.contains(transform.translation) {
+            transform.translation = arena.wrap_position(transform.translation);
+        }
+    }
+}
+
+// 开放世界中把实体限制在区域内，并去掉速度中继续向外的分量
+fn clamp_entities(mut query: Query<(&mut Transform, Option<&mut Velocity>), With<ClampToArena>>, arena: Res<Arena>) {
+    if arena.is_wrapping() {
+        return;
+    }
+    for (mut transform, velocity) in query.iter_mut() {
+        let position = transform.translation;
+        if arena.contains(position) {
+            continue;
+        }
+        let clamped = arena.clamp_position(position);
+        transform.translation = clamped;
+        if let Some(mut velocity) = velocity {
+            let outward = position - clamped;
+            if outward.x * velocity.value.x > 0.0 {
+                velocity.value.x = 0.0;
+            }
+            if outward.z * velocity.value.z > 0.0 {
+                velocity.value.z = 0.0;
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashMap};
+
+use super::{collider_shape::ColliderShape, collision_detection::Collider};
 
 // 定义了一个名为 SceneAssets 的结构体，用于存储游戏中的关键场景资源
 // 这个结构体实现了 Resource 特性，使其可以被注册为 Bevy 应用的全局资源
@@ -11,15 +13,131 @@ pub struct SceneAssets {
     pub missiles: Handle<Scene>,
 }
 
+// 场景的种类，用于按种类查找场景和由场景计算出的碰撞形状
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SceneKind {
+    Asteroid,
+    Spaceship,
+    Missiles,
+}
+
+impl SceneKind {
+    const ALL: [SceneKind; 3] = [SceneKind::Asteroid, SceneKind::Spaceship, SceneKind::Missiles];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "asteroid" => Some(Self::Asteroid),
+            "spaceship" => Some(Self::Spaceship),
+            "missiles" => Some(Self::Missiles),
+            _ => None,
+        }
+    }
+}
+
+impl SceneAssets {
+    pub fn get(&self, kind: SceneKind) -> &Handle<Scene> {
+        match kind {
+            SceneKind::Asteroid => &self.asteroid,
+            SceneKind::Spaceship => &self.spaceship,
+            SceneKind::Missiles => &self.missiles,
+        }
+    }
+}
+
+// 由网格包围盒拟合碰撞形状的方式
+//  - ConvexHull: 所有网格 AABB 角点组成的凸包，最贴合
+//  - Cuboid:     以场景原点为中心、包住所有角点的盒子
+//  - Capsule:    沿本地 Z 轴、包住所有角点的胶囊体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShapeFit {
+    #[default]
+    ConvexHull,
+    Cuboid,
+    Capsule,
+}
+
+impl ShapeFit {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "convex-hull" => Some(Self::ConvexHull),
+            "cuboid" => Some(Self::Cuboid),
+            "capsule" => Some(Self::Capsule),
+            _ => None,
+        }
+    }
+}
+
+// 解析命令行参数 --collider-fit 场景=方式，可以重复多次，例如 --collider-fit spaceship=capsule。
+// 场景是 asteroid|spaceship|missiles，方式是 convex-hull|cuboid|capsule，写错的参数被忽略
+pub fn collider_fit_args() -> Vec<(SceneKind, ShapeFit)> {
+    let mut fits = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--collider-fit" {
+            continue;
+        }
+        let fit = args.next().and_then(|value| {
+            let (kind, fit) = value.split_once('=')?;
+            Some((SceneKind::parse(kind)?, ShapeFit::parse(fit)?))
+        });
+        fits.extend(fit);
+    }
+    fits
+}
+
+// 场景加载完成后，根据场景中各个网格的包围盒（AABB）计算出的碰撞形状
+// auto_shapes 为 false 时不计算，实体保持创建时的碰撞形状；fits 是各个场景的拟合方式，没有写的场景使用凸包
+#[derive(Resource, Debug)]
+pub struct SceneColliders {
+    pub auto_shapes: bool,
+    pub fits: HashMap<SceneKind, ShapeFit>,
+    shapes: HashMap<SceneKind, ColliderShape>,
+}
+
+impl Default for SceneColliders {
+    fn default() -> Self {
+        Self {
+            auto_shapes: true,
+            fits: HashMap::new(),
+            shapes: HashMap::new(),
+        }
+    }
+}
+
+impl SceneColliders {
+    pub fn get(&self, kind: SceneKind) -> Option<&ColliderShape> {
+        self.shapes.get(&kind)
+    }
+
+    pub fn fit(&self, kind: SceneKind) -> ShapeFit {
+        self.fits.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+// 标记组件：实体的碰撞形状在对应场景加载完成后，自动替换为由场景计算出的形状
+// 碰撞层等其它属性保持不变
+#[derive(Component, Debug)]
+pub struct AutoColliderShape(pub SceneKind);
+
 // 定义了一个插件，用于加载和初始化 SceneAssets 资源
-pub struct AssetLoaderPlugin;
+// fits 指定各个场景的碰撞形状由包围盒拟合成哪种形状
+pub struct AssetLoaderPlugin {
+    pub fits: Vec<(SceneKind, ShapeFit)>,
+}
 
 // 初始化 SceneAssets 资源：init_resource::<SceneAssets>() 确保 SceneAssets 资源在应用启动前被初始化
 // load_assets 注册：add_systems(Startup, load_assets) 启动阶段添加 load_assets 系统，用于加载资源
+// compute_scene_colliders 在场景加载完成后计算碰撞形状，apply_scene_colliders 把形状应用到实体上
 impl Plugin for AssetLoaderPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SceneAssets>()
-            .add_systems(Startup, load_assets);
+            .insert_resource(SceneColliders {
+                fits: self.fits.iter().copied().collect(),
+                ..default()
+            })
+            .add_systems(Startup, load_assets)
+            .add_systems(Update, compute_scene_colliders)
+            .add_systems(PostUpdate, apply_scene_colliders);
     }
 }
 
@@ -34,4 +152,106 @@ fn load_assets(mut scene_assets: ResMut<SceneAssets>, asset_server: Res<AssetSer
         spaceship: asset_server.load("spaceship.glb#Scene0"),
         missiles: asset_server.load("Missiles.glb#Scene0"),
     }
-}
\ No newline at end of file
+}
+
+// 每帧检查尚未计算的场景，场景及其网格全部加载完成后计算一次碰撞形状
+fn compute_scene_colliders(
+    mut scene_colliders: ResMut<SceneColliders>,
+    scene_assets: Res<SceneAssets>,
+    scenes: Res<Assets<Scene>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if !scene_colliders.auto_shapes {
+        return;
+    }
+
+    for kind in SceneKind::ALL {
+        if scene_colliders.shapes.contains_key(&kind) {
+            continue;
+        }
+        let Some(scene) = scenes.get(scene_assets.get(kind)) else {
+            continue;
+        };
+        if let Some(shape) = scene_shape(scene, &meshes, scene_colliders.fit(kind)) {
+            info!("Computed {:?} collider from scene bounds, radius {:.2}", kind, shape.bounding_radius());
+            scene_colliders.shapes.insert(kind, shape);
+        }
+    }
+}
+
+// 把场景中每个网格的 AABB 的 8 个角点变换到场景根节点的坐标系下，再按 fit 拟合出形状。
+// 有网格还没有加载完成时返回 None，下一帧再试
+fn scene_shape(scene: &Scene, meshes: &Assets<Mesh>, fit: ShapeFit) -> Option<ColliderShape> {
+    let mut points = Vec::new();
+    for entity in scene.world.iter_entities() {
+        let Some(mesh_handle) = entity.get::<Handle<Mesh>>() else {
+            continue;
+        };
+        let aabb = meshes.get(mesh_handle)?.compute_aabb()?;
+        let transform = scene_transform(&scene.world, entity.id());
+        points.extend(aabb_corners(&aabb).map(|corner| transform.transform_point3(corner)));
+    }
+
+    if points.is_empty() {
+        return None;
+    }
+
+    // 碰撞形状以实体原点为中心，所以盒子和胶囊体在每个轴上取离原点最远的角点
+    let half_extents = points.iter().fold(Vec3::ZERO, |extents, point| extents.max(point.abs()));
+    let shape = match fit {
+        ShapeFit::ConvexHull => ColliderShape::ConvexHull { points },
+        ShapeFit::Cuboid => ColliderShape::Cuboid { half_extents },
+        ShapeFit::Capsule => {
+            let radius = half_extents.x.max(half_extents.y);
+            ColliderShape::Capsule {
+                half_length: (half_extents.z - radius).max(0.0),
+                radius,
+            }
+        }
+    };
+    Some(shape)
+}
+
+// 沿着 Parent 向上累乘 Transform，得到实体相对于场景根节点的变换
+fn scene_transform(world: &World, entity: Entity) -> Mat4 {
+    let mut matrix = Mat4::IDENTITY;
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        let entity_ref = world.entity(entity);
+        if let Some(transform) = entity_ref.get::<Transform>() {
+            matrix = transform.compute_matrix() * matrix;
+        }
+        current = entity_ref.get::<Parent>().map(|parent| parent.get());
+    }
+    matrix
+}
+
+fn aabb_corners(aabb: &Aabb) -> [Vec3; 8] {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    let mut corners = [Vec3::ZERO; 8];
+    for (index, corner) in corners.iter_mut().enumerate() {
+        let sign = Vec3::new(
+            if index & 1 == 0 { -1.0 } else { 1.0 },
+            if index & 2 == 0 { -1.0 } else { 1.0 },
+            if index & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        *corner = center + half_extents * sign;
+    }
+    corners
+}
+
+// 新加入 AutoColliderShape 的实体，或者碰撞形状刚刚计算完成时，替换实体的碰撞形状
+fn apply_scene_colliders(
+    mut query: Query<(Ref<AutoColliderShape>, &mut Collider)>,
+    scene_colliders: Res<SceneColliders>,
+) {
+    for (auto_shape, mut collider) in query.iter_mut() {
+        if !scene_colliders.is_changed() && !auto_shape.is_added() {
+            continue;
+        }
+        if let Some(shape) = scene_colliders.get(auto_shape.0) {
+            collider.set_shape(shape.clone());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    archetypes::ArchetypeTable,
+    rng::{GameRng, RngStream},
+};
+
+// 缓存的网格变体数量，生成小行星时从中随机挑选
+const VARIANT_COUNT: usize = 8;
+// 细分次数 3 有 162 个顶点、320 个三角形，足够表现起伏又不会太贵
+const SUBDIVISIONS: usize = 3;
+// 未变形的半径，与 glTF 模型和碰撞半径的基准一致
+const BASE_RADIUS: f32 = 2.5;
+// 顶点沿法线方向偏移的最大比例
+const ROUGHNESS: f32 = 0.35;
+// 噪声的频率和叠加层数
+const NOISE_FREQUENCY: f32 = 1.2;
+const NOISE_OCTAVES: u32 = 3;
+
+// 小行星使用的模型
+//  - Gltf:       所有小行星共用 Asteroid.glb 的场景
+//  - Procedural: 从网格池中随机挑选程序生成的凹凸不平的二十面体球
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsteroidModel {
+    Gltf,
+    #[default]
+    Procedural,
+}
+
+// 一个网格变体和它的最大半径（用作碰撞半径，碰撞球包住所有凸起）
+#[derive(Debug, Clone)]
+pub struct AsteroidMeshVariant {
+    pub mesh: Handle<Mesh>,
+    pub radius: f32,
+}
+
+// 程序生成的网格池和每个小行星种类颜色对应的材质。
+// 网格池为空（例如没有添加 AsteroidMeshPlugin）时使用 glTF 模型
+#[derive(Resource, Debug, Default)]
+pub struct AsteroidMeshes {
+    pub model: AsteroidModel,
+    variants: Vec<AsteroidMeshVariant>,
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl AsteroidMeshes {
+    // 小行星实际使用程序生成的网格
+    pub fn is_procedural(&self) -> bool {
+        self.model == AsteroidModel::Procedural && !self.variants.is_empty() && !self.materials.is_empty()
+    }
+
+    pub fn pick(&self, archetype: usize, rng: &mut impl Rng) -> Option<(&AsteroidMeshVariant, &Handle<StandardMaterial>)> {
+        if !self.is_procedural() {
+            return None;
+        }
+        let variant = &self.variants[rng.gen_range(0..self.variants.len())];
+        let material = self.materials.get(archetype).unwrap_or(&self.materials[0]);
+        Some((variant, material))
+    }
+}
+
+impl AsteroidModel {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "gltf" => Some(Self::Gltf),
+            "procedural" => Some(Self::Procedural),
+            _ => None,
+        }
+    }
+}
+
+// 解析命令行参数 --asteroid-model gltf|procedural
+pub fn asteroid_model_arg() -> Option<AsteroidModel> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--asteroid-model" {
+            return args.next().and_then(|value| AsteroidModel::parse(&value));
+        }
+    }
+    None
+}
+
+// 启动时用 GameRng 的种子生成网格池，相同的种子得到相同的一组网格，并创建各种类的材质，
+// 这样 PostStartup 预热对象池时就能确定使用哪种模型。
+// model 决定小行星使用哪种模型，整局游戏不变
+pub struct AsteroidMeshPlugin {
+    pub model: AsteroidModel,
+}
+
+impl Plugin for AsteroidMeshPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AsteroidMeshes { model: self.model, ..default() })
+            .add_systems(Startup, (generate_asteroid_meshes, sync_archetype_materials))
+            .add_systems(Update, sync_archetype_materials);
+    }
+}
+
+fn generate_asteroid_meshes(
+    mut asteroid_meshes: ResMut<AsteroidMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = game_rng.stream(RngStream::Meshes);
+    asteroid_meshes.variants = (0..VARIANT_COUNT)
+        .map(|_| {
+            let (mesh, radius) = asteroid_mesh(rng.gen(), SUBDIVISIONS, ROUGHNESS);
+            AsteroidMeshVariant { mesh: meshes.add(mesh), radius }
+        })
+        .collect();
+}
+
+// 种类表变化（包括第一次加载）时，为每个种类的颜色重新创建材质
+fn sync_archetype_materials(
+    mut asteroid_meshes: ResMut<AsteroidMeshes>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    archetypes: Res<ArchetypeTable>,
+) {
+    if !archetypes.is_changed() {
+        return;
+    }
+    asteroid_meshes.materials = archetypes
+        .iter()
+        .map(|archetype| {
+            materials.add(StandardMaterial {
+                base_color: archetype.color(),
+                perceptual_roughness: 0.95,
+                ..default()
+            })
+        })
+        .collect();
+}
+
+// 由种子生成一个凹凸不平的小行星网格：
+// 二十面体球的每个顶点按三维噪声沿径向偏移，然后重新计算平滑法线。
+// 返回网格和顶点到中心的最大距离
+pub fn asteroid_mesh(seed: u64, subdivisions: usize, roughness: f32) -> (Mesh, f32) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    // 每个种子在噪声空间中取不同的区域
+    let offset = Vec3::new(rng.gen_range(-1000.0..1000.0), rng.gen_range(-1000.0..1000.0), rng.gen_range(-1000.0..1000.0));
+    let noise_seed: u32 = rng.gen();
+
+    let mut mesh = Mesh::try_from(shape::Icosphere { radius: 1.0, subdivisions })
+        .expect("icosphere subdivisions within limits");
+
+    let Some(VertexAttributeValues::Float32x3(unit_positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        unreachable!("icosphere positions are Float32x3");
+    };
+    let positions: Vec<Vec3> = unit_positions
+        .iter()
+        .map(|&position| {
+            let direction = Vec3::from(position);
+            let noise = fractal_noise(direction * NOISE_FREQUENCY + offset, noise_seed);
+            direction * BASE_RADIUS * (1.0 + roughness * noise)
+        })
+        .collect();
+    let radius = positions.iter().map(|position| position.length()).fold(0.0, f32::max);
+
+    let indices: Vec<usize> = mesh.indices().map(|indices| indices.iter().collect()).unwrap_or_default();
+    let normals = smooth_normals(&positions, &indices);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions.iter().map(|position| position.to_array()).collect::<Vec<_>>());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals.iter().map(|normal| normal.to_array()).collect::<Vec<_>>());
+    mesh.set_indices(Some(Indices::U32(indices.iter().map(|&index| index as u32).collect())));
+    (mesh, radius)
+}
+
+// 共享同一个顶点的三角形法线按面积加权求和
+fn smooth_normals(positions: &[Vec3], indices: &[usize]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        let face_normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for index in [a, b, c] {
+            normals[index] += face_normal;
+        }
+    }
+    normals
+        .iter()
+        .zip(positions)
+        .map(|(normal, position)| normal.try_normalize().unwrap_or(position.normalize_or_zero()))
+        .collect()
+}
+
+// 多层值噪声叠加，结果大致在 [-1, 1]
+fn fractal_noise(point: Vec3, seed: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut frequency = 1.0;
+    for octave in 0..NOISE_OCTAVES {
+        value += amplitude * value_noise(point * frequency, seed.wrapping_add(octave));
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    value / (1.0 - 0.5f32.powi(NOISE_OCTAVES as i32))
+}
+
+// 晶格点上取随机值，晶格内用平滑插值，结果在 [-1, 1]
+fn value_noise(point: Vec3, seed: u32) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (Vec3::splat(3.0) - 2.0 * t);
+    let cell = cell.as_ivec3();
+
+    let corner = |dx: i32, dy: i32, dz: i32| lattice_value(cell + IVec3::new(dx, dy, dz), seed);
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), t.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), t.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), t.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), t.x);
+    lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z)
+}
+
+fn lattice_value(cell: IVec3, seed: u32) -> f32 {
+    let mut hash = seed
+        ^ (cell.x as u32).wrapping_mul(0x8da6_b343)
+        ^ (cell.y as u32).wrapping_mul(0xd816_3841)
+        ^ (cell.z as u32).wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    hash = hash.wrapping_mul(0x297a_2d39);
+    hash ^= hash >> 15;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
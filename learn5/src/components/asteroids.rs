@@ -1,91 +1,420 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*, render::primitives::Aabb};
 use std::ops::Range;
 use rand::prelude::*;
 
-use super::{asset_loading::SceneAssets, collision_detection::Collider, movement::{Acceleration, MovingObjectBundle, Velocity}};
+use super::{
+    archetypes::{ArchetypeTable, AsteroidBehavior},
+    arena::{Arena, ScreenWrap},
+    asteroid_mesh::AsteroidMeshes,
+    asset_loading::SceneAssets,
+    collision_detection::{Collider, CollisionLayers},
+    collision_response::{Mass, Restitution},
+    health::{DamageDealt, DamageOnContact, EntityDestroyed, Health, HealthSet},
+    movement::{Acceleration, AngularVelocity, MaxSpeed, MotionBundle, Velocity},
+    pool::{EntityPool, PoolPlugin, Poolable},
+    rng::{GameRng, RngStream},
+    schedule::PhysicsSet,
+    score::ScoreValue,
+    spaceship::Spaceship,
+    spawn_placement::{Obstacle, SpawnPlacement, SpawnStrategy},
+    waves::run_wave_director,
+};
 
-const VELOCITY_SCALAR: f32 = 5.0;
 const ACCELERATION_SCALAR: f32 = 1.0;
-const SPAWN_RANGE_X: Range<f32> = -25.0..25.0;
-const SPAWN_RANGE_Z: Range<f32> = 0.0..25.0;
-const SPAWN_TIME_SECONDS: f32 = 1.0;
-const ROTATE_SPEED: f32 = 2.5;
+const SPIN_RATE_RANGE: Range<f32> = 0.5..3.0;
+const MAX_SPEED: f32 = 15.0;
 const RADIUS: f32 = 2.5;
+const MASS: f32 = 10.0;
+const RESTITUTION: f32 = 0.8;
+// 撞到飞船或导弹时造成的伤害，按大小等级的缩放系数放大
+const CONTACT_DAMAGE: f32 = 10.0;
+// 碎片的速度：继承一部分母体速度，再沿分裂方向加速
+const FRAGMENT_INHERITED_VELOCITY: f32 = 0.5;
+const FRAGMENT_SPEED: f32 = 6.0;
+// 碎片方向在撞击方向两侧展开的最大角度（弧度）
+const FRAGMENT_SPREAD_ANGLE: f32 = 0.8;
+const FRAGMENT_COUNT_RANGE: Range<usize> = 2..4;
+// 启动时预先生成的小行星数量
+const ASTEROID_POOL_SIZE: usize = 24;
 
-// Asteroid 是一个标记组件，用于在 ECS 查询中标识小行星实体
+// Asteroid 组件标识小行星实体，archetype 是它在 ArchetypeTable 中的种类，碎片会继承母体的种类
 #[derive(Component, Debug)]
-pub struct Asteroid;
+pub struct Asteroid {
+    pub archetype: usize,
+}
+
+// 小行星被回收时移除的组件。场景、网格和材质保留下来，下次取出时会被覆盖；
+// Aabb 也要移除，换了网格变体之后需要重新计算包围盒
+impl Poolable for Asteroid {
+    type Active = (
+        MotionBundle,
+        Asteroid,
+        AsteroidSize,
+        ScreenWrap,
+        Mass,
+        Restitution,
+        Health,
+        DamageOnContact,
+        ScoreValue,
+        Volatile,
+        Aabb,
+    );
+}
+
+// 被摧毁时会爆炸的小行星，由种类的 AsteroidBehavior::Volatile 决定
+#[derive(Component, Debug)]
+pub struct Volatile {
+    pub radius: f32,
+    pub damage: f32,
+}
+
+// 一次等待结算的爆炸，source 是爆炸的小行星
+#[derive(Debug, Clone, Copy)]
+struct Explosion {
+    position: Vec3,
+    radius: f32,
+    damage: f32,
+    source: Entity,
+}
+
+// 易爆小行星在 HealthSet::Destroyed 中被摧毁，爆炸伤害要等到下一个固定步长的 HealthSet::Damage 才结算。
+// 伤害先存在这里而不是直接发送 DamageDealt：帧率很高时两帧之间可能没有固定步长，事件会在结算之前被丢弃
+#[derive(Resource, Debug, Default)]
+struct PendingExplosions(Vec<Explosion>);
+
+// 小行星的大小等级。大小等级决定模型缩放、碰撞半径和质量，
+// 大的和中等的小行星被摧毁时会分裂成若干个小一级的碎片，小的直接消失
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    pub fn scale(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => 2.0,
+            AsteroidSize::Medium => 1.3,
+            AsteroidSize::Small => 0.8,
+        }
+    }
 
-// SpawnTimer 是一个资源，包含一个计时器 Timer，用于控制小行星的生成频率
-#[derive(Resource, Debug)]
-pub struct SpawnTimer {
-    timer: Timer,
+    pub fn radius(&self) -> f32 {
+        RADIUS * self.scale()
+    }
+
+    pub fn mass(&self) -> f32 {
+        MASS * self.scale().powi(3)
+    }
+
+    // 大的小行星需要多次命中才会被摧毁
+    pub fn health(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => 3.0,
+            AsteroidSize::Medium => 2.0,
+            AsteroidSize::Small => 1.0,
+        }
+    }
+
+    pub fn contact_damage(&self) -> f32 {
+        CONTACT_DAMAGE * self.scale()
+    }
+
+    // 分裂后碎片的大小等级，小的小行星不会再分裂
+    pub fn fragment(&self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Small => None,
+        }
+    }
 }
 
+// 生成一颗小行星的请求，由 WavePlugin 的波次导演按配置发出
+// strategy 为 None 时使用 SpawnPlacement 资源中的默认生成方式，
+// archetype 为 None 或者找不到这个名字时，按各种类的 weight 随机选择
+#[derive(Event, Debug)]
+pub struct SpawnAsteroid {
+    pub size: AsteroidSize,
+    pub speed: f32,
+    pub strategy: Option<SpawnStrategy>,
+    pub archetype: Option<String>,
+}
+
+// 生成小行星需要的资源
+#[derive(SystemParam)]
+struct AsteroidAssets<'w> {
+    scene_assets: Res<'w, SceneAssets>,
+    meshes: Res<'w, AsteroidMeshes>,
+    archetypes: Res<'w, ArchetypeTable>,
+    pool: ResMut<'w, EntityPool<Asteroid>>,
+}
+
+// 生成小行星时要避开的飞船和其它碰撞体
+#[derive(SystemParam)]
+struct SpawnObstacles<'w, 's> {
+    ships: Query<'w, 's, (&'static Transform, &'static Collider), With<Spaceship>>,
+    colliders: Query<'w, 's, (&'static Transform, &'static Collider), Without<Spaceship>>,
+}
+
+impl SpawnObstacles<'_, '_> {
+    // 飞船周围留出 ship_clearance，其它碰撞体周围留出 collider_clearance
+    fn collect(&self, placement: &SpawnPlacement) -> Vec<Obstacle> {
+        self.ships
+            .iter()
+            .map(|(transform, collider)| Obstacle {
+                position: transform.translation,
+                radius: collider.radius + placement.ship_clearance,
+            })
+            .chain(self.colliders.iter().map(|(transform, collider)| Obstacle {
+                position: transform.translation,
+                radius: collider.radius + placement.collider_clearance,
+            }))
+            .collect()
+    }
+}
+
+// 一颗小行星的初始位置和运动
+struct AsteroidLaunch {
+    translation: Vec3,
+    velocity: Vec3,
+    acceleration: Vec3,
+}
 
 pub struct AsteroidPlugin;
 
-// AsteroidPlugin 插件用于初始化 SpawnTimer 资源，
-// 并注册三个系统：生成小行星、旋转小行星和处理小行星的碰撞。
+// AsteroidPlugin 插件注册 SpawnAsteroid 事件，
+// 并注册四个系统：按请求生成小行星（从对象池中取出）、处理被摧毁的小行星（分裂）、记下易爆小行星的爆炸，以及在下一个步长引爆它们。
+// 旋转由 MovementPlugin 根据 AngularVelocity 积分。
+// 伤害由 HealthPlugin 在固定步长的响应阶段结算，分裂紧跟在伤害之后，不会有一帧的延迟。
 impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpawnTimer {
-            timer: Timer::from_seconds(SPAWN_TIME_SECONDS, TimerMode::Repeating),
-        }).add_systems(Update, (spawn_asteroid, rotate_asteroids, handle_asteroid_collisions));
+        app.init_resource::<SpawnPlacement>()
+        .init_resource::<AsteroidMeshes>()
+        .init_resource::<ArchetypeTable>()
+        .init_resource::<PendingExplosions>()
+        .add_plugins(PoolPlugin::<Asteroid>::default())
+        .add_event::<SpawnAsteroid>()
+        .add_systems(PostStartup, warm_asteroid_pool)
+        .add_systems(Update, spawn_asteroid.after(run_wave_director))
+        .add_systems(FixedUpdate, (
+            detonate_pending_explosions.in_set(PhysicsSet::RespondCollisions).before(HealthSet::Damage),
+            (handle_destroyed_asteroids, explode_volatile_asteroids).in_set(HealthSet::Destroyed),
+        ));
     }
 }
 
-// 会周期性地检查计时器，若计时器触发，则生成一个小行星。
-// 小行星的位置随机生成于定义的 X 和 Z 范围内，速度和加速度是基于随机单位向量的
-fn spawn_asteroid(mut commands: Commands, mut spawn_timer: ResMut<SpawnTimer>, time: Res<Time>, scene_assets: Res<SceneAssets>) {
-    spawn_timer.timer.tick(time.delta());
-    if !spawn_timer.timer.just_finished() {
+// 预先生成一批隐藏的小行星，避免游戏中生成实体（以及实例化 glTF 场景）造成卡顿。
+// 程序生成的网格在取出时才插入，预先生成的实例只带空的 PbrBundle
+fn warm_asteroid_pool(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool<Asteroid>>,
+    meshes: Res<AsteroidMeshes>,
+    scene_assets: Res<SceneAssets>,
+) {
+    if meshes.is_procedural() {
+        pool.warm(&mut commands, ASTEROID_POOL_SIZE, PbrBundle::default);
+    } else {
+        let scene = scene_assets.asteroid.clone();
+        pool.warm(&mut commands, ASTEROID_POOL_SIZE, || SceneBundle { scene: scene.clone(), ..default() });
+    }
+}
+
+// 每收到一个 SpawnAsteroid 请求，就生成一个对应大小的小行星。
+// 位置和初始方向由 SpawnPlacement 选取，会避开飞船和已有的碰撞体（包括这一帧刚生成的），
+// 加速度与初始方向一致
+fn spawn_asteroid(
+    mut commands: Commands,
+    mut spawn_events: EventReader<SpawnAsteroid>,
+    obstacles: SpawnObstacles,
+    placement: Res<SpawnPlacement>,
+    arena: Res<Arena>,
+    mut assets: AsteroidAssets,
+    mut game_rng: ResMut<GameRng>,
+) {
+    if spawn_events.is_empty() {
         return;
     }
 
-    // we need to bring in the rand crate.
-    let mut rng = rand::thread_rng();
-
-    let translation = Vec3::new(rng.gen_range(SPAWN_RANGE_X), 0., rng.gen_range(SPAWN_RANGE_Z),);
-
-    let mut random_unit_vector = 
-        || Vec3::new(rng.gen_range(-1.0..1.0), 0., rng.gen_range(-1.0..1.0)).normalize_or_zero();
-    let velocity = random_unit_vector() * VELOCITY_SCALAR;
-    let acceleration = random_unit_vector() * ACCELERATION_SCALAR;
-
-    commands.spawn((
-        MovingObjectBundle{
-            velocity: Velocity::new(velocity),
-            acceleration: Acceleration::new(acceleration),
-            collider: Collider::new(RADIUS),
-            model: SceneBundle {
-                scene: scene_assets.asteroid.clone(),
-                transform: Transform::from_translation(translation),
-                ..default()
-            }
-        }, 
-        Asteroid,
-    ));
+    let mut obstacles = obstacles.collect(&placement);
+
+    let rng = game_rng.stream(RngStream::Spawning);
+    for request in spawn_events.read() {
+        let strategy = request.strategy.unwrap_or(placement.strategy);
+        let archetype = match request.archetype.as_deref().and_then(|name| assets.archetypes.index_of(name)) {
+            Some(index) => index,
+            None => assets.archetypes.choose(&[], rng),
+        };
+        let speed = request.speed * assets.archetypes.get(archetype).random_speed_scale(rng);
+        let radius = request.size.radius();
+        let point = placement.find(strategy, &arena, radius, &obstacles, rng);
+        obstacles.push(Obstacle {
+            position: point.position,
+            radius: radius + placement.collider_clearance,
+        });
+
+        spawn_asteroid_entity(
+            &mut commands,
+            &mut assets,
+            rng,
+            request.size,
+            archetype,
+            AsteroidLaunch {
+                translation: point.position,
+                velocity: point.heading * speed,
+                acceleration: point.heading * ACCELERATION_SCALAR,
+            },
+        );
+    }
 }
 
-// 这个系统会遍历所有带有 Asteroid 组件的实体，根据设定的旋转速度更新它们的旋转状态
-fn rotate_asteroids(mut query: Query<&mut Transform, With<Asteroid>>, time: Res<Time>) {
-    for mut transform in query.iter_mut() {
-        transform.rotate_local_z(ROTATE_SPEED * time.delta_seconds());
+// 生成一个指定大小等级和种类的小行星，旋转轴和旋转速率是随机的，速度不会超过 MAX_SPEED。
+// 生命值和质量由大小等级和种类共同决定。
+// 使用程序生成的网格时，模型是网格和种类颜色的材质，否则是 glTF 场景
+fn spawn_asteroid_entity(
+    commands: &mut Commands,
+    assets: &mut AsteroidAssets,
+    rng: &mut impl Rng,
+    size: AsteroidSize,
+    archetype: usize,
+    launch: AsteroidLaunch,
+) {
+    let spin_axis = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+    let spin_rate = rng.gen_range(SPIN_RATE_RANGE);
+    let definition = assets.archetypes.get(archetype);
+
+    // 使用程序生成的网格时，碰撞半径使用网格的最大半径
+    let picked = assets
+        .meshes
+        .pick(archetype, rng)
+        .map(|(variant, material)| (variant.mesh.clone(), material.clone(), variant.radius * size.scale()));
+    let collider_radius = picked.as_ref().map_or(size.radius(), |(_, _, radius)| *radius);
+
+    let transform = Transform::from_translation(launch.translation).with_scale(Vec3::splat(size.scale()));
+    let motion = MotionBundle {
+        velocity: Velocity::new(launch.velocity),
+        acceleration: Acceleration::new(launch.acceleration),
+        angular_velocity: AngularVelocity::from_axis_angle(spin_axis, spin_rate),
+        max_speed: MaxSpeed::new(MAX_SPEED),
+        collider: Collider::new(collider_radius)
+            .with_layers(CollisionLayers::ASTEROID, CollisionLayers::PLAYER | CollisionLayers::PLAYER_PROJECTILE | CollisionLayers::ASTEROID),
+        ..default()
+    };
+    let active = (
+        Asteroid { archetype },
+        size,
+        ScreenWrap,
+        Mass::new(size.mass() * definition.mass),
+        Restitution::new(RESTITUTION),
+        Health::new(size.health() * definition.health),
+        DamageOnContact::new(size.contact_damage(), CollisionLayers::PLAYER | CollisionLayers::PLAYER_PROJECTILE),
+        ScoreValue(definition.score),
+    );
+    let entity = match picked {
+        // 复用的实体保留着上一次的网格，所以每次都重新插入挑中的网格变体和材质
+        Some((mesh, material, _)) => {
+            let entity = assets.pool.spawn(commands, transform, motion, PbrBundle::default, active);
+            commands.entity(entity).insert((mesh, material));
+            entity
+        }
+        None => {
+            let scene = assets.scene_assets.asteroid.clone();
+            assets.pool.spawn(commands, transform, motion, || SceneBundle { scene, ..default() }, active)
+        }
+    };
+
+    if let AsteroidBehavior::Volatile { radius, damage } = definition.behavior {
+        commands.entity(entity).insert(Volatile { radius, damage });
+    }
+}
+
+// 读取 EntityDestroyed 事件，被摧毁的小行星按大小等级分裂出碎片。
+// 小行星之间的接触由 CollisionDetectionPlugin 的物理响应处理（互相弹开），不会造成伤害。
+// 击中小行星的导弹自身只有 1 点生命值，会被小行星的接触伤害摧毁，不会继续击中刚刚分裂出的碎片
+fn handle_destroyed_asteroids(
+    mut commands: Commands,
+    mut destroyed_events: EventReader<EntityDestroyed>,
+    asteroids: Query<(&Transform, &Velocity, &AsteroidSize, &Asteroid)>,
+    sources: Query<&Transform>,
+    mut assets: AsteroidAssets,
+    arena: Res<Arena>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = game_rng.stream(RngStream::Debris);
+    for event in destroyed_events.read() {
+        let Ok((transform, velocity, size, asteroid)) = asteroids.get(event.entity) else {
+            continue;
+        };
+        let Some(fragment_size) = size.fragment() else {
+            continue;
+        };
+
+        // Fragments fly away from whatever hit the asteroid.
+        let impact_direction = event
+            .source
+            .and_then(|source| sources.get(source).ok())
+            .map_or(Vec3::ZERO, |source| -arena.offset(transform.translation, source.translation));
+        let impact_direction = Vec3::new(impact_direction.x, 0.0, impact_direction.z)
+            .try_normalize()
+            .unwrap_or(Vec3::X);
+        let count = rng.gen_range(FRAGMENT_COUNT_RANGE);
+        for index in 0..count {
+            let t = index as f32 / (count - 1) as f32;
+            let angle = FRAGMENT_SPREAD_ANGLE * (2.0 * t - 1.0);
+            let direction = Quat::from_rotation_y(angle) * impact_direction;
+            spawn_asteroid_entity(
+                &mut commands,
+                &mut assets,
+                rng,
+                fragment_size,
+                asteroid.archetype,
+                AsteroidLaunch {
+                    translation: transform.translation + direction * size.radius(),
+                    velocity: velocity.value * FRAGMENT_INHERITED_VELOCITY + direction * FRAGMENT_SPEED,
+                    acceleration: Vec3::ZERO,
+                },
+            );
+        }
     }
 }
 
-// 检测与小行星碰撞的其他实体。
-// 如果发现碰撞，则将小行星实体从 ECS 中移除
-fn handle_asteroid_collisions(mut commands: Commands, query: Query<(Entity, &Collider), With<Asteroid>>) {
-    for (entity, collider) in query.iter() {
-        for &collided_entity in collider.colliding_entities.iter() {
-            // asteroid collided with another asteroid
-            if query.get(collided_entity).is_ok() {
+// 易爆小行星被摧毁时记下一次爆炸，等下一个固定步长再结算
+fn explode_volatile_asteroids(
+    mut destroyed_events: EventReader<EntityDestroyed>,
+    mut pending: ResMut<PendingExplosions>,
+    volatiles: Query<(&Transform, &Volatile)>,
+) {
+    for event in destroyed_events.read() {
+        let Ok((transform, volatile)) = volatiles.get(event.entity) else {
+            continue;
+        };
+        pending.0.push(Explosion {
+            position: transform.translation,
+            radius: volatile.radius,
+            damage: volatile.damage,
+            source: event.entity,
+        });
+    }
+}
+
+// 在伤害结算之前引爆等待中的爆炸，对爆炸半径内所有有生命值的实体造成伤害（不区分碰撞层）。
+// 被波及的易爆小行星又会留下新的爆炸，所以相邻的易爆小行星会一个步长接一个步长地连锁爆炸
+fn detonate_pending_explosions(
+    mut pending: ResMut<PendingExplosions>,
+    mut damage_events: EventWriter<DamageDealt>,
+    targets: Query<(Entity, &Transform, &Collider), With<Health>>,
+    arena: Res<Arena>,
+) {
+    for explosion in pending.0.drain(..) {
+        for (target, transform, collider) in targets.iter() {
+            if target == explosion.source {
                 continue;
             }
-            // Despawn the asteroid.
-            commands.entity(entity).despawn_recursive();
+            let distance = arena.offset(explosion.position, transform.translation).length();
+            if distance <= explosion.radius + collider.radius {
+                damage_events.send(DamageDealt { target, source: Some(explosion.source), amount: explosion.damage });
+            }
         }
     }
-}
\ No newline at end of file
+}
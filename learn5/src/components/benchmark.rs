@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use bevy::{input::InputPlugin, prelude::*};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    arena::{Arena, ArenaPlugin, ScreenWrap},
+    collision_detection::{BroadPhase, Collider, CollisionDetectionPlugin},
+    movement::{MovementPlugin, Velocity},
+    schedule::SchedulePlugin,
+};
+
+const DEFAULT_COLLIDER_COUNT: usize = 3000;
+const BENCHMARK_TICKS: u32 = 120;
+const BENCHMARK_SEED: u64 = 42;
+const COLLIDER_RADIUS_RANGE: std::ops::Range<f32> = 0.5..2.5;
+const COLLIDER_SPEED: f32 = 10.0;
+
+// 解析命令行参数 --bench-collisions [N]，返回需要生成的碰撞体数量
+pub fn collision_benchmark_arg() -> Option<usize> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--bench-collisions" {
+            let count = args.next().and_then(|value| value.parse().ok());
+            return Some(count.unwrap_or(DEFAULT_COLLIDER_COUNT));
+        }
+    }
+    None
+}
+
+// 无窗口的碰撞检测基准测试：
+// 用相同的随机种子生成 count 个运动的碰撞体，分别使用两种宽相位算法运行相同的固定步数，
+// 比较耗时，并检查两者得到的碰撞数量一致。
+pub fn run_collision_benchmark(count: usize) {
+    println!("collision benchmark: {count} colliders, {BENCHMARK_TICKS} fixed ticks");
+
+    let mut results = Vec::new();
+    for broad_phase in [BroadPhase::BruteForce, BroadPhase::SpatialHash] {
+        let (elapsed, contacts) = run_scenario(broad_phase, count);
+        println!(
+            "  {:<12} {:>10.2} ms/tick  {:>8} contacts",
+            format!("{broad_phase:?}"),
+            elapsed.as_secs_f64() * 1000.0 / BENCHMARK_TICKS as f64,
+            contacts,
+        );
+        results.push((elapsed, contacts));
+    }
+
+    let (brute_force, spatial_hash) = (results[0], results[1]);
+    if brute_force.1 != spatial_hash.1 {
+        println!("  WARNING: broad phases disagree on the number of contacts");
+    }
+    println!(
+        "  speedup: {:.1}x",
+        brute_force.0.as_secs_f64() / spatial_hash.0.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+// 返回运行 BENCHMARK_TICKS 次固定步长的总耗时，以及所有步中检测到的碰撞数量之和
+fn run_scenario(broad_phase: BroadPhase, count: usize) -> (Duration, usize) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, InputPlugin))
+        .add_plugins((SchedulePlugin, ArenaPlugin, MovementPlugin, CollisionDetectionPlugin))
+        .insert_resource(broad_phase);
+
+    let half_extents = app.world.resource::<Arena>().half_extents;
+    let mut rng = StdRng::seed_from_u64(BENCHMARK_SEED);
+    for _ in 0..count {
+        let translation = Vec3::new(
+            rng.gen_range(-half_extents.x..half_extents.x),
+            0.0,
+            rng.gen_range(-half_extents.y..half_extents.y),
+        );
+        let direction = Vec3::new(rng.gen_range(-1.0..1.0), 0.0, rng.gen_range(-1.0..1.0)).normalize_or_zero();
+        app.world.spawn((
+            Transform::from_translation(translation),
+            Velocity::new(direction * COLLIDER_SPEED),
+            Collider::new(rng.gen_range(COLLIDER_RADIUS_RANGE)),
+            ScreenWrap,
+        ));
+    }
+
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    let mut contacts = 0;
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..BENCHMARK_TICKS {
+        app.world.resource_mut::<Time>().advance_by(timestep);
+        let start = Instant::now();
+        app.world.run_schedule(FixedUpdate);
+        elapsed += start.elapsed();
+
+        let mut query = app.world.query::<&Collider>();
+        contacts += query.iter(&app.world).map(|collider| collider.colliding_entities.len()).sum::<usize>();
+    }
+    (elapsed, contacts)
+}
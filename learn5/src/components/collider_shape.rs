@@ -0,0 +1,548 @@
+use bevy::prelude::*;
+
+// GJK 的最大迭代次数，达到上限时视为接触
+const GJK_MAX_ITERATIONS: usize = 32;
+const EPSILON: f32 = 1e-6;
+
+// 碰撞体的几何形状，均以碰撞体中心为原点，使用世界单位（不受 Transform 缩放影响），
+// 随实体的旋转一起旋转
+//  - Sphere:     球体
+//  - Capsule:    胶囊体，中心线段沿本地 Z 轴，长度为 2 * half_length
+//  - Cuboid:     有向包围盒
+//  - ConvexHull: 凸包，由若干顶点定义
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColliderShape {
+    Sphere { radius: f32 },
+    Capsule { half_length: f32, radius: f32 },
+    Cuboid { half_extents: Vec3 },
+    ConvexHull { points: Vec<Vec3> },
+}
+
+impl Default for ColliderShape {
+    fn default() -> Self {
+        Self::Sphere { radius: 0.0 }
+    }
+}
+
+impl ColliderShape {
+    // 包围球半径，宽相位和快速剔除使用
+    pub fn bounding_radius(&self) -> f32 {
+        match self {
+            Self::Sphere { radius } => *radius,
+            Self::Capsule { half_length, radius } => half_length + radius,
+            Self::Cuboid { half_extents } => half_extents.length(),
+            Self::ConvexHull { points } => points.iter().map(|point| point.length()).fold(0.0, f32::max),
+        }
+    }
+
+    // 支撑函数：形状在世界方向 direction 上最远的点，相对于碰撞体中心
+    fn support(&self, rotation: Quat, direction: Vec3) -> Vec3 {
+        let local = rotation.inverse() * direction;
+        let point = match self {
+            Self::Sphere { radius } => local.normalize_or_zero() * *radius,
+            Self::Capsule { half_length, radius } => {
+                Vec3::Z * half_length.copysign(local.z) + local.normalize_or_zero() * *radius
+            }
+            Self::Cuboid { half_extents } => Vec3::new(
+                half_extents.x.copysign(local.x),
+                half_extents.y.copysign(local.y),
+                half_extents.z.copysign(local.z),
+            ),
+            Self::ConvexHull { points } => points
+                .iter()
+                .copied()
+                .max_by(|a, b| a.dot(local).total_cmp(&b.dot(local)))
+                .unwrap_or(Vec3::ZERO),
+        };
+        rotation * point
+    }
+}
+
+// 碰撞体在世界中的位置和朝向
+#[derive(Debug, Clone, Copy)]
+pub struct Pose {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+// 两个形状相交时的接触信息
+// normal 是从 A 指向 B 的单位向量，penetration 是沿 normal 的穿透深度
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeContact {
+    pub normal: Vec3,
+    pub penetration: f32,
+}
+
+impl ShapeContact {
+    pub fn flipped(self) -> Self {
+        Self { normal: -self.normal, ..self }
+    }
+}
+
+// 窄相位：判断两个形状是否相交，相交时返回接触信息。
+// 球体和胶囊体之间、球体和盒子之间有解析解，接触信息是精确的；
+// 其它组合使用 GJK 算法判断相交，接触法线取两个中心的连线，穿透深度是两者在该方向上投影的重叠长度
+pub fn shape_contact(a: &ColliderShape, pose_a: Pose, b: &ColliderShape, pose_b: Pose) -> Option<ShapeContact> {
+    use ColliderShape::*;
+
+    match (a, b) {
+        (Sphere { radius: radius_a }, Sphere { radius: radius_b }) => {
+            sphere_contact(pose_a.position, *radius_a, pose_b.position, *radius_b)
+        }
+        (Sphere { radius: sphere_radius }, Capsule { half_length, radius }) => {
+            let (start, end) = capsule_segment(*half_length, pose_b);
+            let closest = closest_point_on_segment(pose_a.position, start, end);
+            sphere_contact(pose_a.position, *sphere_radius, closest, *radius)
+        }
+        (Capsule { .. }, Sphere { .. }) => shape_contact(b, pose_b, a, pose_a).map(ShapeContact::flipped),
+        (Capsule { half_length: half_length_a, radius: radius_a }, Capsule { half_length: half_length_b, radius: radius_b }) => {
+            let (start_a, end_a) = capsule_segment(*half_length_a, pose_a);
+            let (start_b, end_b) = capsule_segment(*half_length_b, pose_b);
+            let (closest_a, closest_b) = closest_points_between_segments(start_a, end_a, start_b, end_b);
+            sphere_contact(closest_a, *radius_a, closest_b, *radius_b)
+        }
+        (Sphere { radius }, Cuboid { half_extents }) => sphere_cuboid_contact(pose_a.position, *radius, *half_extents, pose_b),
+        (Cuboid { .. }, Sphere { .. }) => shape_contact(b, pose_b, a, pose_a).map(ShapeContact::flipped),
+        _ => {
+            if !gjk_intersect(a, pose_a, b, pose_b) {
+                return None;
+            }
+            let offset = pose_b.position - pose_a.position;
+            let normal = offset.try_normalize().unwrap_or(Vec3::X);
+            let reach_a = a.support(pose_a.rotation, normal).dot(normal);
+            let reach_b = -b.support(pose_b.rotation, -normal).dot(normal);
+            // 只是表面相切时 GJK 也报告相交，和解析解一样视为不相交
+            let penetration = reach_a + reach_b - offset.dot(normal);
+            if penetration <= 0.0 {
+                return None;
+            }
+            Some(ShapeContact { normal, penetration })
+        }
+    }
+}
+
+fn sphere_contact(center_a: Vec3, radius_a: f32, center_b: Vec3, radius_b: f32) -> Option<ShapeContact> {
+    let offset = center_b - center_a;
+    let distance = offset.length();
+    if distance >= radius_a + radius_b {
+        return None;
+    }
+    Some(ShapeContact {
+        normal: offset.try_normalize().unwrap_or(Vec3::X),
+        penetration: radius_a + radius_b - distance,
+    })
+}
+
+// 球心在盒子外时，法线指向盒子上离球心最近的点；
+// 球心在盒子内时，沿穿透最浅的面把球推出去
+fn sphere_cuboid_contact(center: Vec3, radius: f32, half_extents: Vec3, pose: Pose) -> Option<ShapeContact> {
+    let local = pose.rotation.inverse() * (center - pose.position);
+    let closest = local.clamp(-half_extents, half_extents);
+    let outside = closest - local;
+    let distance = outside.length();
+
+    if distance > EPSILON {
+        if distance >= radius {
+            return None;
+        }
+        return Some(ShapeContact {
+            normal: pose.rotation * (outside / distance),
+            penetration: radius - distance,
+        });
+    }
+
+    let depth = half_extents - local.abs();
+    let (axis, face_depth) = if depth.x <= depth.y && depth.x <= depth.z {
+        (Vec3::X * local.x.signum(), depth.x)
+    } else if depth.y <= depth.z {
+        (Vec3::Y * local.y.signum(), depth.y)
+    } else {
+        (Vec3::Z * local.z.signum(), depth.z)
+    };
+    Some(ShapeContact {
+        normal: pose.rotation * -axis,
+        penetration: radius + face_depth,
+    })
+}
+
+fn capsule_segment(half_length: f32, pose: Pose) -> (Vec3, Vec3) {
+    let axis = pose.rotation * Vec3::Z * half_length;
+    (pose.position - axis, pose.position + axis)
+}
+
+fn closest_point_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared < EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+// 两条线段之间最近的两个点（Ericson,《Real-Time Collision Detection》5.1.9）
+fn closest_points_between_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a < EPSILON && e < EPSILON {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a < EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e < EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+            let mut s = if denominator > EPSILON { ((b * f - c * e) / denominator).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+// GJK：两个凸形状相交，当且仅当它们的闵可夫斯基差 A - B 包含原点。
+// 单纯形按从新到旧的顺序保存，simplex[0] 总是最新加入的点
+fn gjk_intersect(a: &ColliderShape, pose_a: Pose, b: &ColliderShape, pose_b: Pose) -> bool {
+    let support = |direction: Vec3| {
+        (pose_a.position + a.support(pose_a.rotation, direction))
+            - (pose_b.position + b.support(pose_b.rotation, -direction))
+    };
+
+    let mut direction = pose_b.position - pose_a.position;
+    if direction.length_squared() < EPSILON {
+        direction = Vec3::X;
+    }
+
+    let mut simplex: Vec<Vec3> = Vec::with_capacity(4);
+    let first = support(direction);
+    simplex.push(first);
+    direction = -first;
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        if direction.length_squared() < EPSILON {
+            // The origin lies on the current simplex.
+            return true;
+        }
+        let point = support(direction);
+        if point.dot(direction) < 0.0 {
+            return false;
+        }
+        simplex.insert(0, point);
+        if update_simplex(&mut simplex, &mut direction) {
+            return true;
+        }
+    }
+    true
+}
+
+// 根据原点所在的 Voronoi 区域化简单纯形并给出新的搜索方向，单纯形包含原点时返回 true
+fn update_simplex(simplex: &mut Vec<Vec3>, direction: &mut Vec3) -> bool {
+    match simplex.len() {
+        2 => update_line(simplex, direction),
+        3 => update_triangle(simplex, direction),
+        _ => update_tetrahedron(simplex, direction),
+    }
+}
+
+fn update_line(simplex: &mut Vec<Vec3>, direction: &mut Vec3) -> bool {
+    let (a, b) = (simplex[0], simplex[1]);
+    let ab = b - a;
+    let ao = -a;
+    if ab.dot(ao) > 0.0 {
+        *direction = ab.cross(ao).cross(ab);
+    } else {
+        simplex.truncate(1);
+        *direction = ao;
+    }
+    false
+}
+
+fn update_triangle(simplex: &mut Vec<Vec3>, direction: &mut Vec3) -> bool {
+    let (a, b, c) = (simplex[0], simplex[1], simplex[2]);
+    let ab = b - a;
+    let ac = c - a;
+    let ao = -a;
+    let abc = ab.cross(ac);
+
+    if abc.cross(ac).dot(ao) > 0.0 {
+        if ac.dot(ao) > 0.0 {
+            *simplex = vec![a, c];
+            *direction = ac.cross(ao).cross(ac);
+            return false;
+        }
+        *simplex = vec![a, b];
+        return update_line(simplex, direction);
+    }
+
+    if ab.cross(abc).dot(ao) > 0.0 {
+        *simplex = vec![a, b];
+        return update_line(simplex, direction);
+    }
+
+    let side = abc.dot(ao);
+    if side > 0.0 {
+        *direction = abc;
+    } else if side < 0.0 {
+        *simplex = vec![a, c, b];
+        *direction = -abc;
+    } else {
+        // The origin lies inside the triangle.
+        return true;
+    }
+    false
+}
+
+fn update_tetrahedron(simplex: &mut Vec<Vec3>, direction: &mut Vec3) -> bool {
+    let (a, b, c, d) = (simplex[0], simplex[1], simplex[2], simplex[3]);
+    let ao = -a;
+    let abc = (b - a).cross(c - a);
+    let acd = (c - a).cross(d - a);
+    let adb = (d - a).cross(b - a);
+
+    if abc.dot(ao) > 0.0 {
+        *simplex = vec![a, b, c];
+        return update_triangle(simplex, direction);
+    }
+    if acd.dot(ao) > 0.0 {
+        *simplex = vec![a, c, d];
+        return update_triangle(simplex, direction);
+    }
+    if adb.dot(ao) > 0.0 {
+        *simplex = vec![a, d, b];
+        return update_triangle(simplex, direction);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-4;
+    // 和边界的距离小于这个值时，GJK 的迭代和解析解的舍入可能给出不同的结论，比较时跳过
+    const BOUNDARY_MARGIN: f32 = 0.05;
+    const RANDOM_SAMPLES: usize = 2000;
+
+    fn at(x: f32, y: f32, z: f32) -> Pose {
+        Pose { position: Vec3::new(x, y, z), rotation: Quat::IDENTITY }
+    }
+
+    fn sphere(radius: f32) -> ColliderShape {
+        ColliderShape::Sphere { radius }
+    }
+
+    fn capsule(half_length: f32, radius: f32) -> ColliderShape {
+        ColliderShape::Capsule { half_length, radius }
+    }
+
+    fn cuboid(x: f32, y: f32, z: f32) -> ColliderShape {
+        ColliderShape::Cuboid { half_extents: Vec3::new(x, y, z) }
+    }
+
+    // 和 cuboid(half, half, half) 形状相同的凸包
+    fn cube_hull(half: f32) -> ColliderShape {
+        let points = (0..8)
+            .map(|index| {
+                Vec3::new(
+                    if index & 1 == 0 { -half } else { half },
+                    if index & 2 == 0 { -half } else { half },
+                    if index & 4 == 0 { -half } else { half },
+                )
+            })
+            .collect();
+        ColliderShape::ConvexHull { points }
+    }
+
+    // expected 是期望的法线和穿透深度，为 None 时应当不相交
+    fn assert_contact(a: &ColliderShape, pose_a: Pose, b: &ColliderShape, pose_b: Pose, expected: Option<(Vec3, f32)>) {
+        let contact = shape_contact(a, pose_a, b, pose_b);
+        match (contact, expected) {
+            (None, None) => {}
+            (Some(contact), Some((normal, penetration))) => {
+                assert!(contact.normal.abs_diff_eq(normal, TOLERANCE), "normal {:?}, expected {:?}", contact.normal, normal);
+                assert!(
+                    (contact.penetration - penetration).abs() < TOLERANCE,
+                    "penetration {}, expected {}",
+                    contact.penetration,
+                    penetration
+                );
+            }
+            (contact, expected) => panic!("{a:?} at {pose_a:?} vs {b:?} at {pose_b:?}: got {contact:?}, expected {expected:?}"),
+        }
+        // 交换 A 和 B 之后法线反向
+        let swapped = shape_contact(b, pose_b, a, pose_a);
+        assert_eq!(swapped.is_some(), expected.is_some());
+        if let (Some(swapped), Some((normal, _))) = (swapped, expected) {
+            assert!(swapped.normal.abs_diff_eq(-normal, TOLERANCE));
+        }
+    }
+
+    // 所有尺寸向外扩张 margin，用来判断一对形状离边界是否足够远
+    fn inflate(shape: &ColliderShape, margin: f32) -> ColliderShape {
+        match shape {
+            ColliderShape::Sphere { radius } => sphere(radius + margin),
+            ColliderShape::Capsule { half_length, radius } => capsule(*half_length, radius + margin),
+            ColliderShape::Cuboid { half_extents } => ColliderShape::Cuboid { half_extents: *half_extents + margin },
+            ColliderShape::ConvexHull { .. } => unreachable!("only analytic shapes are inflated"),
+        }
+    }
+
+    fn has_analytic_contact(a: &ColliderShape, b: &ColliderShape) -> bool {
+        use ColliderShape::*;
+        matches!((a, b), (Sphere { .. }, _) | (_, Sphere { .. }) | (Capsule { .. }, Capsule { .. }))
+    }
+
+    #[test]
+    fn sphere_sphere() {
+        let a = sphere(1.0);
+        let b = sphere(2.0);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(2.5, 0.0, 0.0), Some((Vec3::X, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 3.5, 0.0), None);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 0.0, 3.0), None);
+    }
+
+    #[test]
+    fn sphere_capsule() {
+        let a = sphere(1.0);
+        let b = capsule(2.0, 1.0);
+        // 胶囊体中段的侧面
+        assert_contact(&a, at(1.5, 0.0, 1.0), &b, at(0.0, 0.0, 0.0), Some((-Vec3::X, 0.5)));
+        // 端部的半球
+        assert_contact(&a, at(0.0, 0.0, 3.5), &b, at(0.0, 0.0, 0.0), Some((-Vec3::Z, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 4.5), &b, at(0.0, 0.0, 0.0), None);
+        assert_contact(&a, at(0.0, 2.0, -2.0), &b, at(0.0, 0.0, 0.0), None);
+        // 胶囊体转到 X 轴上之后，原来在端部外面的球碰到了侧面
+        let rotated = Pose { position: Vec3::ZERO, rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) };
+        assert_contact(&a, at(0.0, 1.5, 0.0), &b, rotated, Some((-Vec3::Y, 0.5)));
+        assert_contact(&a, at(3.5, 0.0, 0.0), &b, rotated, Some((-Vec3::X, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 3.5), &b, rotated, None);
+    }
+
+    #[test]
+    fn capsule_capsule() {
+        let a = capsule(2.0, 0.5);
+        let b = capsule(2.0, 0.5);
+        let across = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        // 十字交叉，中心线段相距 0.8
+        let crossing = Pose { position: Vec3::new(0.0, 0.8, 0.0), rotation: across };
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, crossing, Some((Vec3::Y, 0.2)));
+        // 平行错开，只有端部的半球重叠
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 0.0, 4.5), Some((Vec3::Z, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(1.5, 0.0, 0.0), None);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(1.0, 0.0, 1.0), None);
+    }
+
+    #[test]
+    fn sphere_cuboid() {
+        let a = sphere(1.0);
+        let b = cuboid(2.0, 1.0, 1.0);
+        assert_contact(&a, at(2.5, 0.0, 0.0), &b, at(0.0, 0.0, 0.0), Some((-Vec3::X, 0.5)));
+        assert_contact(&a, at(0.0, 3.0, 0.0), &b, at(0.0, 0.0, 0.0), None);
+        assert_contact(&a, at(3.0, 0.0, 0.0), &b, at(0.0, 0.0, 0.0), None);
+        // 对着棱的方向，离盒子最近的点是棱上的点
+        let corner = Vec3::new(2.0, 1.0, 0.0) + Vec3::new(1.0, 1.0, 0.0).normalize() * 0.8;
+        assert_contact(&a, at(corner.x, corner.y, corner.z), &b, at(0.0, 0.0, 0.0), Some((-Vec3::new(1.0, 1.0, 0.0).normalize(), 0.2)));
+        // 球心在盒子里时，从穿透最浅的面推出去
+        assert_contact(&a, at(0.0, 0.75, 0.0), &b, at(0.0, 0.0, 0.0), Some((-Vec3::Y, 1.25)));
+        // 转过 90 度之后长边朝向 Z
+        let rotated = Pose { position: Vec3::ZERO, rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) };
+        assert_contact(&a, at(0.0, 0.0, 2.5), &b, rotated, Some((-Vec3::Z, 0.5)));
+        assert_contact(&a, at(2.5, 0.0, 0.0), &b, rotated, None);
+    }
+
+    #[test]
+    fn cuboid_cuboid() {
+        let a = cuboid(1.0, 1.0, 1.0);
+        let b = cuboid(1.0, 1.0, 1.0);
+        // GJK 的接触法线取两个中心的连线
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(1.5, 0.0, 0.0), Some((Vec3::X, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(2.5, 0.5, 0.0), None);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 0.0, 2.0), None);
+        // 转过 45 度之后角朝向 A，对角线长度 sqrt(2)
+        let rotated = Pose { position: Vec3::new(2.3, 0.0, 0.0), rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_4) };
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, rotated, Some((Vec3::X, std::f32::consts::SQRT_2 - 1.3)));
+        let rotated = Pose { position: Vec3::new(2.5, 0.0, 0.0), ..rotated };
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, rotated, None);
+    }
+
+    #[test]
+    fn capsule_cuboid() {
+        let a = capsule(2.0, 0.5);
+        let b = cuboid(1.0, 1.0, 1.0);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 0.0, 3.0), Some((Vec3::Z, 0.5)));
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 1.2, 0.0), Some((Vec3::Y, 0.3)));
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(0.0, 0.0, 4.0), None);
+        assert_contact(&a, at(0.0, 0.0, 0.0), &b, at(2.0, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn convex_hull_matches_cuboid() {
+        let hull = cube_hull(1.0);
+        let a = sphere(1.0);
+        assert_contact(&hull, at(0.0, 0.0, 0.0), &a, at(1.5, 0.0, 0.0), Some((Vec3::X, 0.5)));
+        assert_contact(&hull, at(0.0, 0.0, 0.0), &a, at(0.0, 2.5, 0.0), None);
+        assert_contact(&hull, at(0.0, 0.0, 0.0), &a, at(0.0, 0.0, 2.0), None);
+        assert_contact(&hull, at(0.0, 0.0, 0.0), &hull, at(1.5, 0.0, 0.0), Some((Vec3::X, 0.5)));
+        assert_contact(&hull, at(0.0, 0.0, 0.0), &hull, at(0.0, 2.0, 0.0), None);
+    }
+
+    // GJK 对解析解覆盖的组合也应当给出相同的相交结论
+    #[test]
+    fn gjk_agrees_with_analytic_contacts() {
+        let shapes = [sphere(1.0), capsule(1.5, 0.5), cuboid(1.5, 0.5, 1.0)];
+        let mut rng = StdRng::seed_from_u64(7);
+        for a in &shapes {
+            for b in &shapes {
+                if !has_analytic_contact(a, b) {
+                    continue;
+                }
+                let mut checked = 0;
+                for _ in 0..RANDOM_SAMPLES {
+                    let pose_a = Pose {
+                        position: Vec3::new(rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0)),
+                        rotation: Quat::from_euler(EulerRot::XYZ, rng.gen_range(0.0..6.3), rng.gen_range(0.0..6.3), rng.gen_range(0.0..6.3)),
+                    };
+                    let pose_b = Pose {
+                        position: Vec3::new(rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0)),
+                        rotation: Quat::from_euler(EulerRot::XYZ, rng.gen_range(0.0..6.3), rng.gen_range(0.0..6.3), rng.gen_range(0.0..6.3)),
+                    };
+                    let analytic = shape_contact(a, pose_a, b, pose_b);
+                    let near_boundary = match analytic {
+                        Some(contact) => contact.penetration < BOUNDARY_MARGIN,
+                        None => shape_contact(&inflate(a, BOUNDARY_MARGIN), pose_a, b, pose_b).is_some(),
+                    };
+                    if near_boundary {
+                        continue;
+                    }
+                    assert_eq!(
+                        gjk_intersect(a, pose_a, b, pose_b),
+                        analytic.is_some(),
+                        "{a:?} at {pose_a:?} vs {b:?} at {pose_b:?}"
+                    );
+                    checked += 1;
+                }
+                assert!(checked > RANDOM_SAMPLES / 2, "{a:?} vs {b:?}: only {checked} samples away from the boundary");
+            }
+        }
+    }
+}
@@ -1,57 +1,323 @@
-use bevy::{prelude::*, utils::hashbrown::HashMap};
+use std::ops::{BitAnd, BitOr};
 
-// Collider 组件定义了一个具有半径和正在碰撞的实体列表的碰撞器
-// radius 属性表示碰撞检测时的半径。
+use bevy::{ecs::system::SystemParam, prelude::*, utils::hashbrown::HashMap};
+
+use super::{
+    arena::Arena,
+    collider_shape::{shape_contact, ColliderShape, Pose, ShapeContact},
+    collision_response::{resolve_collisions, CollisionResponse},
+    continuous_collision::{sweep_fast_movers, SweptCollision},
+    schedule::PhysicsSet,
+    spatial_hash::SpatialHash,
+};
+
+// 碰撞层位掩码，每一位代表一类实体，可以用 | 组合多个层
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionLayers(pub u32);
+
+impl CollisionLayers {
+    pub const NONE: Self = Self(0);
+    pub const PLAYER: Self = Self(1 << 0);
+    pub const PLAYER_PROJECTILE: Self = Self(1 << 1);
+    pub const ASTEROID: Self = Self(1 << 2);
+    pub const ENEMY: Self = Self(1 << 3);
+    pub const PICKUP: Self = Self(1 << 4);
+    pub const ALL: Self = Self(u32::MAX);
+
+    pub fn intersects(self, other: Self) -> bool {
+        (self & other) != Self::NONE
+    }
+}
+
+impl BitOr for CollisionLayers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for CollisionLayers {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+// Collider 组件定义了一个具有形状、半径和正在碰撞的实体列表的碰撞器
+// shape 属性表示碰撞体的几何形状，窄相位使用。
+// radius 属性是形状的包围球半径，宽相位和快速剔除使用。
+// membership 表示该碰撞器属于哪些层，filter 表示它会与哪些层发生碰撞。
 // colliding_entities 用来存储与该实体碰撞的其他实体的列表。
 #[derive(Component, Debug)]
 pub struct Collider {
     pub radius: f32,
+    pub shape: ColliderShape,
+    pub membership: CollisionLayers,
+    pub filter: CollisionLayers,
     pub colliding_entities: Vec<Entity>,
 }
 
-// 提供了一个构造函数来创建 Collider 实例
+// 提供了构造函数来创建 Collider 实例，默认属于所有层并与所有层碰撞，
 // 同时初始化 colliding_entities 为一个空的向量。
+// new 创建球形碰撞体，from_shape 可以使用任意形状。
 impl Collider {
     pub fn new(radius: f32) -> Self {
+        Self::from_shape(ColliderShape::Sphere { radius })
+    }
+
+    pub fn from_shape(shape: ColliderShape) -> Self {
         Self {
-            radius,
+            radius: shape.bounding_radius(),
+            shape,
+            membership: CollisionLayers::ALL,
+            filter: CollisionLayers::ALL,
             colliding_entities: vec![],
         }
     }
+
+    pub fn with_layers(mut self, membership: CollisionLayers, filter: CollisionLayers) -> Self {
+        self.membership = membership;
+        self.filter = filter;
+        self
+    }
+
+    pub fn set_shape(&mut self, shape: ColliderShape) {
+        self.radius = shape.bounding_radius();
+        self.shape = shape;
+    }
+
+    // 只有双方的 filter 都包含对方的 membership 时才会发生碰撞
+    pub fn can_collide_with(&self, other: &Collider) -> bool {
+        self.filter.intersects(other.membership) && other.filter.intersects(self.membership)
+    }
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+// 一对碰撞实体，entity_a 总是两者中较小的那个，所以同一对实体只有一种表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContactPair {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+}
+
+impl ContactPair {
+    pub fn new(a: Entity, b: Entity) -> Self {
+        if a < b {
+            Self { entity_a: a, entity_b: b }
+        } else {
+            Self { entity_a: b, entity_b: a }
+        }
+    }
+}
+
+// 两个实体开始接触时发送一次
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionStarted(pub ContactPair);
+
+// 两个实体停止接触时发送一次。
+// 如果接触是因为其中一个实体被销毁而结束的，事件中的实体可能已经不存在了
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionEnded(pub ContactPair);
+
+// 持续的接触信息
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    // 接触持续的时间（秒）
+    pub duration: f32,
+    // 从 ContactPair::entity_a 指向 entity_b 的单位法线
+    pub normal: Vec3,
+    // 沿法线的穿透深度
+    pub penetration: f32,
 }
 
-// CollisionDetectionPlugin 插件注册了一个名为 collision_detection 的系统
+// Contacts 资源记录了当前所有正在接触的实体对，可以用来查询“持续接触”
+#[derive(Resource, Debug, Default)]
+pub struct Contacts {
+    contacts: HashMap<ContactPair, Contact>,
+}
+
+impl Contacts {
+    pub fn iter(&self) -> impl Iterator<Item = (&ContactPair, &Contact)> {
+        self.contacts.iter()
+    }
+
+    pub fn contains(&self, pair: &ContactPair) -> bool {
+        self.contacts.contains_key(pair)
+    }
+
+    // 记录一个在离散检测之外发现的接触（例如连续碰撞检测），已存在时返回 false
+    pub fn begin(&mut self, pair: ContactPair, contact: Contact) -> bool {
+        if self.contacts.contains_key(&pair) {
+            return false;
+        }
+        self.contacts.insert(pair, contact);
+        true
+    }
+
+    // 立即移除一个实体的所有接触（例如实体被放回对象池），返回被移除的实体对
+    pub fn remove_entity(&mut self, entity: Entity) -> Vec<ContactPair> {
+        let pairs: Vec<ContactPair> = self
+            .contacts
+            .keys()
+            .filter(|pair| pair.entity_a == entity || pair.entity_b == entity)
+            .copied()
+            .collect();
+        for pair in &pairs {
+            self.contacts.remove(pair);
+        }
+        pairs
+    }
+}
+
+// 宽相位算法
+//  - BruteForce:  两两比较所有碰撞体，O(n²)，保留用于对比和调试
+//  - SpatialHash: 均匀网格，只比较相同或相邻格子中的碰撞体
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroadPhase {
+    BruteForce,
+    #[default]
+    SpatialHash,
+}
+
+// 宽相位每一步复用的网格和候选实体对，避免重复分配内存
+#[derive(Default)]
+struct BroadPhaseScratch {
+    grid: SpatialHash,
+    pairs: Vec<(usize, usize)>,
+}
+
+// 接触开始和结束的事件
+#[derive(SystemParam)]
+struct ContactEvents<'w> {
+    started: EventWriter<'w, CollisionStarted>,
+    ended: EventWriter<'w, CollisionEnded>,
+}
+
+// CollisionDetectionPlugin 插件在固定步长的检测阶段注册了一个名为 collision_detection 的系统，
+// 随后为 FastMover 运行连续碰撞检测 sweep_fast_movers，
+// 并在响应阶段注册了可选的物理响应系统 resolve_collisions（由 CollisionResponse 资源控制开关）
 pub struct CollisionDetectionPlugin;
 
 impl Plugin for CollisionDetectionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, collision_detection);
+        app.init_resource::<BroadPhase>()
+            .init_resource::<Contacts>()
+            .init_resource::<CollisionResponse>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<SweptCollision>()
+            .add_systems(FixedUpdate, (
+                (collision_detection, sweep_fast_movers).chain().in_set(PhysicsSet::DetectCollisions),
+                resolve_collisions.in_set(PhysicsSet::RespondCollisions),
+            ));
     }
 }
 
-// 检测碰撞：系统遍历所有带有 GlobalTransform 和 Collider 组件的实体，
-//  - 计算每对实体之间的距离，如果距离小于两者半径之和，就将它们记录为碰撞
+// 检测碰撞：系统遍历所有带有 Transform 和 Collider 组件的实体，
+//  - 使用 Transform 而不是 GlobalTransform，因为 GlobalTransform 要到 PostUpdate 才会传播，
+//    在固定步长中读取它会落后一帧
+//  - 宽相位先找出可能相交的实体对，每一对只检查一次
+//  - 碰撞层不匹配的实体对在窄相位之前就被过滤掉
+//  - 窄相位先比较包围球：如果距离小于两者半径之和，再按照两者的形状精确判断是否相交
+//  - 距离通过 Arena::offset 计算，环形世界中靠近接缝两侧的实体也会被视为相邻
 // 更新碰撞：在碰撞检测后，系统将为每个实体更新其 colliding_entities 列表，以包含所有与之碰撞的实体
-fn collision_detection(mut query: Query<(Entity, &GlobalTransform, &mut Collider)>) {
-    let mut colliding_entities: HashMap<Entity, Vec<Entity>> = HashMap::new();
+// 更新接触：与上一步的接触比较，为新出现的接触发送 CollisionStarted，为消失的接触发送 CollisionEnded
+fn collision_detection(
+    mut query: Query<(Entity, &Transform, &mut Collider)>,
+    mut contacts: ResMut<Contacts>,
+    mut events: ContactEvents,
+    time: Res<Time>,
+    arena: Res<Arena>,
+    broad_phase: Res<BroadPhase>,
+    mut scratch: Local<BroadPhaseScratch>,
+) {
+    let BroadPhaseScratch { grid, pairs } = &mut *scratch;
+    let bodies: Vec<(Entity, &Transform, &Collider)> = query.iter().collect();
 
-    // First phase: Detect Collisions.
-    for (entity_a, transform_a, collider_a) in query.iter() {
-        for (entity_b, transform_b, collider_b) in query.iter() {
-            if entity_a != entity_b {
-                let distance = transform_a.translation().distance(transform_b.translation());
-                if distance < collider_a.radius + collider_b.radius {
-                    colliding_entities.entry(entity_a).or_insert_with(Vec::new).push(entity_b);
+    // First phase: Broad phase.
+    match *broad_phase {
+        BroadPhase::BruteForce => {
+            pairs.clear();
+            for i in 0..bodies.len() {
+                for j in (i + 1)..bodies.len() {
+                    pairs.push((i, j));
                 }
             }
         }
+        BroadPhase::SpatialHash => {
+            let max_radius = bodies.iter().fold(0.0_f32, |max, &(_, _, collider)| max.max(collider.radius));
+            grid.reset(max_radius, &arena);
+            for (index, &(_, transform, _)) in bodies.iter().enumerate() {
+                grid.insert(index, transform.translation);
+            }
+            grid.candidate_pairs(pairs);
+        }
     }
 
-    // Second phase: Update Collisions.
+    // Second phase: Narrow phase.
+    let mut colliding_entities: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut current_pairs: HashMap<ContactPair, ShapeContact> = HashMap::new();
+    for &(i, j) in pairs.iter() {
+        let (entity_a, transform_a, collider_a) = bodies[i];
+        let (entity_b, transform_b, collider_b) = bodies[j];
+        if !collider_a.can_collide_with(collider_b) {
+            continue;
+        }
+        let offset = arena.offset(transform_a.translation, transform_b.translation);
+        if offset.length() >= collider_a.radius + collider_b.radius {
+            continue;
+        }
+        // Shapes are tested relative to entity A so wrapped neighbours line up across the seams.
+        let pose_a = Pose { position: Vec3::ZERO, rotation: transform_a.rotation };
+        let pose_b = Pose { position: offset, rotation: transform_b.rotation };
+        if let Some(contact) = shape_contact(&collider_a.shape, pose_a, &collider_b.shape, pose_b) {
+            colliding_entities.entry(entity_a).or_default().push(entity_b);
+            colliding_entities.entry(entity_b).or_default().push(entity_a);
+            let pair = ContactPair::new(entity_a, entity_b);
+            // The contact normal must point from pair.entity_a to pair.entity_b.
+            let contact = if pair.entity_a == entity_a { contact } else { contact.flipped() };
+            current_pairs.insert(pair, contact);
+        }
+    }
+
+    // Third phase: Update Collisions.
     for (entity, _, mut collider) in query.iter_mut() {
         collider.colliding_entities.clear();
         if let Some(collisions) = colliding_entities.get(&entity) {
             collider.colliding_entities.extend(collisions.iter().copied());
         }
     }
-}
\ No newline at end of file
+
+    // Fourth phase: Update Contacts.
+    contacts.contacts.retain(|pair, _| {
+        let still_touching = current_pairs.contains_key(pair);
+        if !still_touching {
+            events.ended.send(CollisionEnded(*pair));
+        }
+        still_touching
+    });
+    for (pair, shape_contact) in current_pairs {
+        match contacts.contacts.get_mut(&pair) {
+            Some(contact) => {
+                contact.duration += time.delta_seconds();
+                contact.normal = shape_contact.normal;
+                contact.penetration = shape_contact.penetration;
+            }
+            None => {
+                contacts.contacts.insert(pair, Contact {
+                    duration: 0.0,
+                    normal: shape_contact.normal,
+                    penetration: shape_contact.penetration,
+                });
+                events.started.send(CollisionStarted(pair));
+            }
+        }
+    }
+}
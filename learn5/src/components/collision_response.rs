@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use super::{collision_detection::Contacts, movement::Velocity};
+
+// 位置修正时允许的穿透深度，避免物体在接触面上来回抖动
+const PENETRATION_SLOP: f32 = 0.01;
+// 每一步修正的穿透比例
+const CORRECTION_PERCENT: f32 = 0.8;
+const DEFAULT_RESTITUTION: f32 = 0.5;
+
+// 质量，只有接触双方都有 Mass 时才会进行物理响应
+#[derive(Component, Debug)]
+pub struct Mass {
+    pub value: f32,
+}
+
+impl Mass {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+
+    fn inverse(&self) -> f32 {
+        if self.value > 0.0 { 1.0 / self.value } else { 0.0 }
+    }
+}
+
+// 恢复系数：0 为完全非弹性碰撞，1 为完全弹性碰撞。没有该组件时使用 DEFAULT_RESTITUTION
+#[derive(Component, Debug)]
+pub struct Restitution {
+    pub value: f32,
+}
+
+impl Restitution {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+// 碰撞响应阶段的开关，关闭后接触只会产生事件，不会改变速度和位置
+#[derive(Resource, Debug)]
+pub struct CollisionResponse {
+    pub enabled: bool,
+}
+
+impl Default for CollisionResponse {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+// 遍历当前所有接触，对双方都有 Mass 的实体对：
+//  - 施加冲量，使两者沿接触法线方向的相对速度按恢复系数反弹
+//  - 按质量的倒数分配位置修正，把重叠的物体分开
+// 游戏在 XZ 平面上进行，所以法线会投影到 XZ 平面上
+pub fn resolve_collisions(
+    mut query: Query<(&mut Transform, &mut Velocity, &Mass, Option<&Restitution>)>,
+    contacts: Res<Contacts>,
+    response: Res<CollisionResponse>,
+) {
+    if !response.enabled {
+        return;
+    }
+
+    for (pair, contact) in contacts.iter() {
+        let Ok([mut body_a, mut body_b]) = query.get_many_mut([pair.entity_a, pair.entity_b]) else {
+            continue;
+        };
+
+        let Some(normal) = Vec3::new(contact.normal.x, 0.0, contact.normal.z).try_normalize() else {
+            continue;
+        };
+        let inverse_mass_a = body_a.2.inverse();
+        let inverse_mass_b = body_b.2.inverse();
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+        if inverse_mass_sum <= 0.0 {
+            continue;
+        }
+
+        // Only resolve bodies that are moving towards each other.
+        let relative_velocity = body_b.1.value - body_a.1.value;
+        let normal_speed = relative_velocity.dot(normal);
+        if normal_speed < 0.0 {
+            let restitution_a = body_a.3.map_or(DEFAULT_RESTITUTION, |restitution| restitution.value);
+            let restitution_b = body_b.3.map_or(DEFAULT_RESTITUTION, |restitution| restitution.value);
+            let restitution = restitution_a.min(restitution_b);
+            let impulse = normal * (-(1.0 + restitution) * normal_speed / inverse_mass_sum);
+            body_a.1.value -= impulse * inverse_mass_a;
+            body_b.1.value += impulse * inverse_mass_b;
+        }
+
+        let correction = normal * ((contact.penetration - PENETRATION_SLOP).max(0.0) / inverse_mass_sum * CORRECTION_PERCENT);
+        body_a.0.translation -= correction * inverse_mass_a;
+        body_b.0.translation += correction * inverse_mass_b;
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use super::{
+    arena::Arena,
+    collision_detection::{Collider, CollisionStarted, Contact, ContactPair, Contacts},
+    movement::Velocity,
+};
+
+// 标记组件：高速移动的实体（例如导弹）。
+// 离散检测只比较每一步结束时的位置，低帧率时小而快的物体可能直接穿过目标，
+// 所以对 FastMover 还会沿着这一步的运动轨迹做扫掠球检测
+#[derive(Component, Debug)]
+pub struct FastMover;
+
+// 连续碰撞检测发现的命中
+//  - time_of_impact: 命中发生在这一步开始后的多少秒
+//  - point:          命中时的接触点
+//  - normal:         命中时从 pair.entity_a 指向 pair.entity_b 的单位法线
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SweptCollision {
+    pub pair: ContactPair,
+    pub time_of_impact: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+// 参与扫掠检测的碰撞体，FastMover 标记需要沿轨迹检测的一方
+type SweepBodies<'w, 's> = Query<'w, 's, (Entity, &'static Transform, Option<&'static Velocity>, &'static mut Collider, Has<FastMover>)>;
+
+// 对每个 FastMover，把它和其它碰撞体都看作包围球，
+// 用两者在这一步中的相对运动求出第一次接触的时间（解 |s0 + d·t| = r_a + r_b）。
+// 命中且离散检测没有发现这对接触时，记录接触、写入 colliding_entities，
+// 并发送 CollisionStarted 和 SweptCollision，下一步接触消失时会照常收到 CollisionEnded。
+// 位置在这一步中按 p1 = p0 + v·dt 积分，所以起点由当前位置和速度倒推，环形世界的环绕不会影响轨迹
+pub fn sweep_fast_movers(
+    mut colliders: SweepBodies,
+    mut contacts: ResMut<Contacts>,
+    mut started_events: EventWriter<CollisionStarted>,
+    mut swept_events: EventWriter<SweptCollision>,
+    arena: Res<Arena>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    let mut hits: Vec<(Entity, Entity)> = Vec::new();
+
+    // Movers and targets come from the same query, since both sides need the Collider.
+    for (mover, mover_transform, mover_velocity, mover_collider, fast) in colliders.iter() {
+        let (true, Some(mover_velocity)) = (fast, mover_velocity) else {
+            continue;
+        };
+        for (target, target_transform, target_velocity, target_collider, _) in colliders.iter() {
+            if target == mover || !mover_collider.can_collide_with(target_collider) {
+                continue;
+            }
+            let pair = ContactPair::new(mover, target);
+            if contacts.contains(&pair) {
+                continue;
+            }
+
+            // Relative motion of the mover as seen from the target.
+            let target_velocity = target_velocity.map_or(Vec3::ZERO, |velocity| velocity.value);
+            let displacement = (mover_velocity.value - target_velocity) * dt;
+            let end = -arena.offset(mover_transform.translation, target_transform.translation);
+            let start = end - displacement;
+            let radius = mover_collider.radius + target_collider.radius;
+
+            let Some(t) = time_of_impact(start, displacement, radius) else {
+                continue;
+            };
+
+            // At the time of impact the mover sits at `separation` from the target.
+            let separation = start + displacement * t;
+            let normal_from_mover = (-separation).try_normalize().unwrap_or(Vec3::X);
+            let mover_position = mover_transform.translation - mover_velocity.value * dt * (1.0 - t);
+            let point = mover_position + normal_from_mover * mover_collider.radius;
+            let normal = if pair.entity_a == mover { normal_from_mover } else { -normal_from_mover };
+
+            let contact = Contact {
+                duration: 0.0,
+                normal,
+                penetration: 0.0,
+            };
+            if contacts.begin(pair, contact) {
+                started_events.send(CollisionStarted(pair));
+                swept_events.send(SweptCollision {
+                    pair,
+                    time_of_impact: t * dt,
+                    point,
+                    normal,
+                });
+                hits.push((mover, target));
+            }
+        }
+    }
+
+    for (mover, target) in hits {
+        if let Ok([(_, _, _, mut mover_collider, _), (_, _, _, mut target_collider, _)]) = colliders.get_many_mut([mover, target]) {
+            mover_collider.colliding_entities.push(target);
+            target_collider.colliding_entities.push(mover);
+        }
+    }
+}
+
+// 射线检测：从 origin 沿单位向量 direction 发出长度为 max_distance 的射线，返回第一个命中的碰撞体和命中距离。
+// probe 的碰撞层决定射线能命中哪些碰撞体。和扫掠检测一样把碰撞体看作包围球，
+// 距离通过 Arena::offset 计算，环形世界中射线会命中接缝另一侧最近的镜像。
+// 起点已经在包围球内的碰撞体视为在距离 0 处命中
+pub fn raycast<'a>(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    probe: &Collider,
+    colliders: impl IntoIterator<Item = (Entity, &'a Transform, &'a Collider)>,
+    arena: &Arena,
+) -> Option<(Entity, f32)> {
+    colliders
+        .into_iter()
+        .filter(|(_, _, collider)| probe.can_collide_with(collider))
+        .filter_map(|(entity, transform, collider)| {
+            let start = -arena.offset(origin, transform.translation);
+            if start.length_squared() <= collider.radius * collider.radius {
+                return Some((entity, 0.0));
+            }
+            let t = time_of_impact(start, direction * max_distance, collider.radius)?;
+            Some((entity, t * max_distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+// 求 |start + displacement·t| = radius 在 [0, 1] 内最小的解。
+// 起点已经重叠的情况由离散检测处理，这里返回 None
+fn time_of_impact(start: Vec3, displacement: Vec3, radius: f32) -> Option<f32> {
+    let a = displacement.length_squared();
+    let b = 2.0 * start.dot(displacement);
+    let c = start.length_squared() - radius * radius;
+    if c <= 0.0 || a <= f32::EPSILON || b >= 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&t).then_some(t)
+}
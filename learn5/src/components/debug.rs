@@ -1,10 +1,28 @@
 use bevy::prelude::*;
 
-pub struct DebugPlugin;
+use super::{
+    collision_detection::{CollisionEnded, CollisionStarted, Contacts},
+    continuous_collision::SweptCollision,
+    health::{DamageDealt, EntityDestroyed, Health},
+    score::Score,
+};
+
+// 解析命令行参数 --debug
+pub fn debug_arg() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--debug")
+}
+
+// enabled 为 false 时什么都不注册，调试时用 --debug 启用
+pub struct DebugPlugin {
+    pub enabled: bool,
+}
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, print_position);
+        if !self.enabled {
+            return;
+        }
+        app.add_systems(Update, (print_position, print_contacts, print_collision_events, print_damage_events, print_score));
     }
 }
 
@@ -16,3 +34,56 @@ fn print_position(query: Query<(Entity, &Transform)>) {
         );
     }
 }
+
+fn print_contacts(contacts: Res<Contacts>) {
+    for (pair, contact) in contacts.iter() {
+        info!(
+            "Entities {:?} and {:?} in contact for {:.2}s",
+            pair.entity_a, pair.entity_b, contact.duration
+        );
+    }
+}
+
+fn print_collision_events(
+    mut started_events: EventReader<CollisionStarted>,
+    mut ended_events: EventReader<CollisionEnded>,
+    mut swept_events: EventReader<SweptCollision>,
+) {
+    for CollisionStarted(pair) in started_events.read() {
+        info!("Collision started: {:?} <-> {:?}", pair.entity_a, pair.entity_b);
+    }
+    for CollisionEnded(pair) in ended_events.read() {
+        info!("Collision ended: {:?} <-> {:?}", pair.entity_a, pair.entity_b);
+    }
+    for hit in swept_events.read() {
+        info!(
+            "Swept hit: {:?} <-> {:?} after {:.4}s at {:?} (normal {:?})",
+            hit.pair.entity_a, hit.pair.entity_b, hit.time_of_impact, hit.point, hit.normal
+        );
+    }
+}
+
+fn print_damage_events(
+    mut damage_events: EventReader<DamageDealt>,
+    mut destroyed_events: EventReader<EntityDestroyed>,
+    health: Query<&Health>,
+) {
+    for event in damage_events.read() {
+        match health.get(event.target) {
+            Ok(health) => info!(
+                "Damage: {:?} hit {:?} for {} ({}/{})",
+                event.source, event.target, event.amount, health.current, health.max
+            ),
+            Err(_) => info!("Damage: {:?} hit {:?} for {}", event.source, event.target, event.amount),
+        }
+    }
+    for event in destroyed_events.read() {
+        info!("Destroyed: {:?} by {:?}", event.entity, event.source);
+    }
+}
+
+fn print_score(score: Res<Score>) {
+    if score.is_changed() && !score.is_added() {
+        info!("Score: {}", score.value);
+    }
+}
@@ -1,27 +1,72 @@
 use bevy::prelude::*;
 
+use super::{arena::{Arena, ClampToArena, DespawnOutsideArena, ScreenWrap}, pool::{Pooled, RecycleCommandsExt}, schedule::PhysicsSet};
+
 const DESPAWN_DISTANCE: f32 = 100.0;
 
+// 寿命：计时结束后销毁实体
+#[derive(Component, Debug)]
+pub struct Lifetime {
+    pub timer: Timer,
+}
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self { timer: Timer::from_seconds(seconds, TimerMode::Once) }
+    }
+}
+
+// 按距离判断是否销毁的实体：顶层实体，不包括池中闲置的实体和限制在区域内的实体
+type DespawnCandidates<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, Has<DespawnOutsideArena>, Has<ScreenWrap>),
+    (Without<Parent>, Without<Pooled>, Without<ClampToArena>),
+>;
+
 // 这里定义了 DespawnPlugin 结构体，用作插件的实现基础。
 pub struct DespawnPlugin;
 
 impl Plugin for DespawnPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, despawn_far_away_entities);
+        app.add_systems(FixedUpdate, (despawn_far_away_entities, despawn_expired_entities).in_set(PhysicsSet::DespawnEntities));
     }
 }
 
 
-//  遍历所有带有 GlobalTransform 组件的实体，并计算每个实体的位置与原点 (Vec3::ZERO) 的距离。
-// 如果这个距离大于 DESPAWN_DISTANCE，则使用 Commands 结构体的 entity() 和 despawn_recursive() 方法
-// 来销毁该实体及其所有子实体
-fn despawn_far_away_entities(mut commands: Commands, query: Query<(Entity, &GlobalTransform)>) {
-    for (entity, transform) in query.iter() {
-        let distance = transform.translation().distance(Vec3::ZERO);
+// 遍历所有顶层实体（子实体会随父实体一起被销毁），并按照它们的标记组件决定是否销毁：
+//  - DespawnOutsideArena：离开游戏区域就销毁
+//  - ScreenWrap：环形世界中由 ArenaPlugin 负责环绕，不会被销毁
+//  - ClampToArena：开放世界中由 ArenaPlugin 限制在区域内，不会被销毁（例如飞船）
+//  - 其它实体：计算与原点 (Vec3::ZERO) 的距离，大于 DESPAWN_DISTANCE 时销毁
+// 使用 Commands 结构体的 entity() 和 recycle_or_despawn() 方法来销毁该实体及其所有子实体，对象池中的实体会被回收。
+// 池中闲置的实体不参与判断
+fn despawn_far_away_entities(
+    mut commands: Commands,
+    query: DespawnCandidates,
+    arena: Res<Arena>,
+) {
+    for (entity, transform, despawn_outside, wraps) in query.iter() {
+        let far_away = if despawn_outside {
+            !arena.contains(transform.translation)
+        } else if wraps && arena.is_wrapping() {
+            false
+        } else {
+            // Entity is far away from the camera's viewport.
+            transform.translation.distance(Vec3::ZERO) > DESPAWN_DISTANCE
+        };
+
+        if far_away {
+            commands.entity(entity).recycle_or_despawn();
+        }
+    }
+}
 
-        // Entity is far away from the camera's viewport.
-        if distance > DESPAWN_DISTANCE {
-            commands.entity(entity).despawn_recursive();
+// 寿命到期的实体在固定步长的销毁阶段中被销毁
+fn despawn_expired_entities(mut commands: Commands, mut query: Query<(Entity, &mut Lifetime)>, time: Res<Time>) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        if lifetime.timer.tick(time.delta()).finished() {
+            commands.entity(entity).recycle_or_despawn();
         }
     }
 }
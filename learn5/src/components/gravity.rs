@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use super::{
+    arena::Arena,
+    movement::{update_velocity, Acceleration},
+    schedule::PhysicsSet,
+};
+
+// 按 G 放置的黑洞：位置、引力强度、作用范围、软化距离和显示半径
+const BLACK_HOLE_POSITION: Vec3 = Vec3::new(0.0, 0.0, 15.0);
+const BLACK_HOLE_STRENGTH: f32 = 2500.0;
+const BLACK_HOLE_RANGE: f32 = 45.0;
+const BLACK_HOLE_SOFTENING: f32 = 4.0;
+const BLACK_HOLE_VISUAL_RADIUS: f32 = 2.0;
+
+// 引力源（行星、黑洞）。
+// 范围 range 内的实体受到 strength / r² 的加速度，r 小于 softening 时按 softening 计算，避免在中心附近无限大
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GravitySource {
+    pub strength: f32,
+    pub range: f32,
+    pub softening: f32,
+}
+
+impl GravitySource {
+    // 在 offset（从实体指向引力源）处产生的加速度
+    pub fn acceleration(&self, offset: Vec3) -> Vec3 {
+        let distance = offset.length();
+        if distance > self.range || distance <= f32::EPSILON {
+            return Vec3::ZERO;
+        }
+        let softened = distance.max(self.softening);
+        offset / distance * self.strength / (softened * softened)
+    }
+}
+
+// 受引力影响的比例，没有该组件时为 1，0 表示不受引力影响
+#[derive(Component, Debug)]
+pub struct GravityScale(pub f32);
+
+// 在 position 处放置一个显示半径为 radius 的引力井（例如黑洞）
+#[derive(Event, Debug)]
+pub struct SpawnGravityWell {
+    pub position: Vec3,
+    pub source: GravitySource,
+    pub radius: f32,
+}
+
+impl SpawnGravityWell {
+    // 按 G 放置的黑洞
+    pub fn black_hole() -> Self {
+        Self {
+            position: BLACK_HOLE_POSITION,
+            source: GravitySource {
+                strength: BLACK_HOLE_STRENGTH,
+                range: BLACK_HOLE_RANGE,
+                softening: BLACK_HOLE_SOFTENING,
+            },
+            radius: BLACK_HOLE_VISUAL_RADIUS,
+        }
+    }
+}
+
+// 引力井共用的单位球网格和材质，按显示半径缩放
+#[derive(Resource, Debug, Default)]
+struct GravityWellAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct GravityPlugin;
+
+// 引力在积分加速度阶段中、速度积分之前施加。
+// 其它系统发送 SpawnGravityWell 事件放置任意配置的引力井
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GravityWellAssets>()
+            .add_event::<SpawnGravityWell>()
+            .add_systems(Startup, create_gravity_well_assets)
+            .add_systems(Update, (gravity_well_controls, spawn_gravity_wells).chain())
+            .add_systems(FixedUpdate, apply_gravity.before(update_velocity).in_set(PhysicsSet::IntegrateAcceleration));
+    }
+}
+
+// 把所有引力源的加速度累加到有 Acceleration 的实体的外力加速度上（引力源不会吸引自己）。
+// 引力不修改持续的加速度，这样飞船的推力和小行星的初始加速度保持不变
+fn apply_gravity(
+    sources: Query<(Entity, &Transform, &GravitySource)>,
+    mut bodies: Query<(Entity, &Transform, &mut Acceleration, Option<&GravityScale>)>,
+    arena: Res<Arena>,
+) {
+    if sources.is_empty() {
+        return;
+    }
+    for (entity, transform, mut body_acceleration, scale) in bodies.iter_mut() {
+        let scale = scale.map_or(1.0, |scale| scale.0);
+        if scale == 0.0 {
+            continue;
+        }
+        let acceleration: Vec3 = sources
+            .iter()
+            .filter(|(source, _, _)| *source != entity)
+            .map(|(_, source_transform, source)| {
+                source.acceleration(arena.offset(transform.translation, source_transform.translation))
+            })
+            .sum();
+        body_acceleration.external += acceleration * scale;
+    }
+}
+
+fn create_gravity_well_assets(
+    mut assets: ResMut<GravityWellAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    assets.mesh = meshes.add(
+        Mesh::try_from(shape::Icosphere { radius: 1.0, subdivisions: 3 }).expect("icosphere subdivisions within limits"),
+    );
+    assets.material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    });
+}
+
+// G 放置一个黑洞，已有引力井时移除所有引力井
+fn gravity_well_controls(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    wells: Query<Entity, With<GravitySource>>,
+    mut spawn_events: EventWriter<SpawnGravityWell>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::G) {
+        return;
+    }
+
+    if !wells.is_empty() {
+        for entity in wells.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        info!("Gravity wells removed");
+        return;
+    }
+
+    spawn_events.send(SpawnGravityWell::black_hole());
+}
+
+fn spawn_gravity_wells(
+    mut commands: Commands,
+    mut spawn_events: EventReader<SpawnGravityWell>,
+    assets: Res<GravityWellAssets>,
+) {
+    for event in spawn_events.read() {
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(event.position).with_scale(Vec3::splat(event.radius)),
+                ..default()
+            },
+            event.source,
+        ));
+        info!("Gravity well placed at {:?}", event.position);
+    }
+}
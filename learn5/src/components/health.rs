@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use super::{
+    collision_detection::{Collider, CollisionLayers, CollisionStarted},
+    pool::RecycleCommandsExt,
+    schedule::PhysicsSet,
+};
+
+// 生命值，降到 0 时实体被摧毁
+#[derive(Component, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+// 接触伤害：开始接触时，对 targets 层中有 Health 的实体造成 amount 点伤害
+#[derive(Component, Debug)]
+pub struct DamageOnContact {
+    pub amount: f32,
+    pub targets: CollisionLayers,
+}
+
+impl DamageOnContact {
+    pub fn new(amount: f32, targets: CollisionLayers) -> Self {
+        Self { amount, targets }
+    }
+}
+
+// 标记组件：带有该组件的实体不会受到任何伤害
+#[derive(Component, Debug)]
+pub struct Invulnerable;
+
+// 可以自动恢复的护盾：伤害先扣除护盾，护盾挡不住的部分才扣除生命值。
+// 受到伤害后 recharge_delay 秒内不会恢复，之后每秒恢复 recharge_rate 点，直到 max
+#[derive(Component, Debug)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    pub recharge_rate: f32,
+    pub recharge_delay: f32,
+    since_damage: f32,
+}
+
+impl Shield {
+    pub fn new(max: f32, recharge_rate: f32, recharge_delay: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            recharge_rate,
+            recharge_delay,
+            since_damage: 0.0,
+        }
+    }
+
+    // 吸收伤害，返回护盾挡不住的部分
+    fn absorb(&mut self, amount: f32) -> f32 {
+        self.since_damage = 0.0;
+        let absorbed = amount.min(self.current);
+        self.current -= absorbed;
+        amount - absorbed
+    }
+}
+
+// 一次伤害。除了接触伤害，其它系统（例如武器）也可以直接发送这个事件
+#[derive(Event, Debug)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+}
+
+// 实体的生命值降到 0 并被销毁。
+// 事件发出时实体的销毁命令还没有执行，HealthSet::Destroyed 中的系统依然可以查询它的组件
+#[derive(Event, Debug)]
+pub struct EntityDestroyed {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+}
+
+// 生命值相关的系统在碰撞响应阶段中按顺序执行：
+// 造成伤害 -> 对被摧毁的实体做出反应（例如小行星分裂）
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HealthSet {
+    Damage,
+    Destroyed,
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageDealt>()
+            .add_event::<EntityDestroyed>()
+            .configure_sets(
+                FixedUpdate,
+                (HealthSet::Damage, HealthSet::Destroyed)
+                    .chain()
+                    .in_set(PhysicsSet::RespondCollisions),
+            )
+            .add_systems(FixedUpdate, (
+                recharge_shields.before(HealthSet::Damage).in_set(PhysicsSet::RespondCollisions),
+                (deal_contact_damage, apply_damage).chain().in_set(HealthSet::Damage),
+            ));
+    }
+}
+
+// 把 CollisionStarted 转换成 DamageDealt，接触双方可以同时伤害对方
+fn deal_contact_damage(
+    mut collision_events: EventReader<CollisionStarted>,
+    mut damage_events: EventWriter<DamageDealt>,
+    dealers: Query<&DamageOnContact>,
+    targets: Query<&Collider, With<Health>>,
+) {
+    for CollisionStarted(pair) in collision_events.read() {
+        for (source, target) in [(pair.entity_a, pair.entity_b), (pair.entity_b, pair.entity_a)] {
+            let (Ok(damage), Ok(collider)) = (dealers.get(source), targets.get(target)) else {
+                continue;
+            };
+            if collider.membership.intersects(damage.targets) {
+                damage_events.send(DamageDealt { target, source: Some(source), amount: damage.amount });
+            }
+        }
+    }
+}
+
+// 扣除护盾和生命值，生命值降到 0 的实体发送 EntityDestroyed 并销毁（对象池中的实体被回收）。
+// 已经降到 0 的实体不再处理，所以同一步中的多次伤害只会摧毁它一次
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageDealt>,
+    mut destroyed_events: EventWriter<EntityDestroyed>,
+    mut query: Query<(&mut Health, Option<&mut Shield>), Without<Invulnerable>>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, shield)) = query.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+        let amount = match shield {
+            Some(mut shield) => shield.absorb(event.amount),
+            None => event.amount,
+        };
+        health.current -= amount;
+        if health.is_dead() {
+            destroyed_events.send(EntityDestroyed { entity: event.target, source: event.source });
+            commands.entity(event.target).recycle_or_despawn();
+        }
+    }
+}
+
+fn recharge_shields(mut query: Query<&mut Shield>, time: Res<Time>) {
+    let dt = time.delta_seconds();
+    for mut shield in query.iter_mut() {
+        shield.since_damage += dt;
+        if shield.since_damage >= shield.recharge_delay && shield.current < shield.max {
+            shield.current = (shield.current + shield.recharge_rate * dt).min(shield.max);
+        }
+    }
+}
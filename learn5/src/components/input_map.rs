@@ -0,0 +1,370 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// 按键映射的配置文件，放在用户配置目录下的 CONFIG_DIR_NAME 目录中。
+// 文件不存在时使用默认映射，重新绑定完成后写入
+const CONFIG_DIR_NAME: &str = "learn5";
+const INPUT_MAP_FILE: &str = "input_map.ron";
+// 按钮类动作的值超过这个阈值时视为按下（模拟扳机、摇杆）
+const PRESS_THRESHOLD: f32 = 0.5;
+// 开始重新绑定和跳过当前动作的按键，这两个键本身不能被绑定
+const REBIND_KEY: KeyCode = KeyCode::F1;
+const SKIP_KEY: KeyCode = KeyCode::Escape;
+
+// 抽象的游戏动作。Thrust、Turn、Strafe、Roll 是轴（-1 到 1），其它是按钮
+//  - Thrust: 正值前进，负值反推
+//  - Turn:   正值右转
+//  - Strafe: 正值向右平移
+//  - Roll:   正值向右滚转
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Thrust,
+    Turn,
+    Strafe,
+    Roll,
+    Fire,
+    Reload,
+    NextWeapon,
+    PreviousWeapon,
+    SelectWeapon(usize),
+    ToggleFlightMode,
+    ToggleDampeners,
+    NextShip,
+}
+
+// 输入来源。按键和鼠标按钮的值是 0 或 1，手柄按钮的值在 0 到 1 之间（模拟扳机），
+// 手柄摇杆的值在 -1 到 1 之间（已经由 Bevy 处理过死区）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputSource {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    GamepadAxis(GamepadAxisType),
+}
+
+impl InputSource {
+    // 手柄摇杆只能在配置文件中设置，重新绑定时保留
+    fn is_analog(&self) -> bool {
+        matches!(self, InputSource::GamepadAxis(_))
+    }
+
+    // 键盘和鼠标算一类，手柄按钮算一类。重新绑定时只替换同一类的绑定
+    fn is_gamepad(&self) -> bool {
+        matches!(self, InputSource::GamepadButton(_) | InputSource::GamepadAxis(_))
+    }
+}
+
+// 一个绑定：输入来源的值乘以 scale 后累加到动作上，轴的两个方向用正负 scale 区分
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub source: InputSource,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl Binding {
+    fn new(action: Action, source: InputSource, scale: f32) -> Self {
+        Self { action, source, scale }
+    }
+}
+
+// 所有动作的绑定，一个动作可以有多个绑定
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct InputMap {
+    pub bindings: Vec<Binding>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Action::*;
+        use InputSource::{GamepadAxis as Stick, GamepadButton as Pad, Key, Mouse};
+
+        let mut bindings = vec![
+            Binding::new(Thrust, Key(KeyCode::W), 1.0),
+            Binding::new(Thrust, Key(KeyCode::S), -1.0),
+            Binding::new(Thrust, Stick(GamepadAxisType::LeftStickY), 1.0),
+            Binding::new(Turn, Key(KeyCode::D), 1.0),
+            Binding::new(Turn, Key(KeyCode::A), -1.0),
+            Binding::new(Turn, Stick(GamepadAxisType::LeftStickX), 1.0),
+            Binding::new(Strafe, Key(KeyCode::E), 1.0),
+            Binding::new(Strafe, Key(KeyCode::Q), -1.0),
+            Binding::new(Strafe, Stick(GamepadAxisType::RightStickX), 1.0),
+            Binding::new(Roll, Key(KeyCode::ControlLeft), 1.0),
+            Binding::new(Roll, Key(KeyCode::ShiftLeft), -1.0),
+            Binding::new(Roll, Pad(GamepadButtonType::DPadRight), 1.0),
+            Binding::new(Roll, Pad(GamepadButtonType::DPadLeft), -1.0),
+            Binding::new(Fire, Key(KeyCode::Space), 1.0),
+            Binding::new(Fire, Mouse(MouseButton::Left), 1.0),
+            Binding::new(Fire, Pad(GamepadButtonType::RightTrigger2), 1.0),
+            Binding::new(Reload, Key(KeyCode::R), 1.0),
+            Binding::new(Reload, Pad(GamepadButtonType::West), 1.0),
+            Binding::new(NextWeapon, Mouse(MouseButton::Right), 1.0),
+            Binding::new(NextWeapon, Pad(GamepadButtonType::RightTrigger), 1.0),
+            Binding::new(PreviousWeapon, Pad(GamepadButtonType::LeftTrigger), 1.0),
+            Binding::new(ToggleFlightMode, Key(KeyCode::Tab), 1.0),
+            Binding::new(ToggleFlightMode, Pad(GamepadButtonType::Select), 1.0),
+            Binding::new(ToggleDampeners, Key(KeyCode::Z), 1.0),
+            Binding::new(ToggleDampeners, Pad(GamepadButtonType::North), 1.0),
+            Binding::new(NextShip, Key(KeyCode::C), 1.0),
+            Binding::new(NextShip, Pad(GamepadButtonType::Start), 1.0),
+        ];
+        let number_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+        bindings.extend(
+            number_keys
+                .into_iter()
+                .enumerate()
+                .map(|(index, key)| Binding::new(SelectWeapon(index), Key(key), 1.0)),
+        );
+        Self { bindings }
+    }
+}
+
+impl InputMap {
+    // 配置文件不存在或者无法解析时使用默认映射
+    fn load() -> Self {
+        let Some(path) = input_map_path() else {
+            return Self::default();
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match ron::from_str(&text) {
+            Ok(map) => {
+                info!("Loaded input map from {}", path.display());
+                map
+            }
+            Err(error) => {
+                warn!("Failed to parse {}, using the default input map: {error}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = input_map_path() else {
+            warn!("No config directory found, the input map is not saved");
+            return;
+        };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                std::fs::write(&path, text).map_err(|error| error.to_string())
+            });
+        match result {
+            Ok(()) => info!("Saved input map to {}", path.display()),
+            Err(error) => warn!("Failed to save {}: {error}", path.display()),
+        }
+    }
+
+    // 把 action 在 scale 方向上的同类（键盘鼠标或手柄按钮）绑定替换成 source。
+    // source 原来绑定在其它动作或方向上时解除那些绑定，一个输入只触发一个动作
+    pub fn rebind(&mut self, action: Action, scale: f32, source: InputSource) {
+        self.bindings.retain(|binding| {
+            let same_slot = binding.action == action
+                && binding.scale.signum() == scale.signum()
+                && !binding.source.is_analog()
+                && binding.source.is_gamepad() == source.is_gamepad();
+            !same_slot && binding.source != source
+        });
+        self.bindings.push(Binding::new(action, source, scale));
+    }
+
+    // 可以重新绑定的 (动作, 方向)，按默认映射和当前映射中第一次出现的顺序排列。
+    // 包括默认映射是为了让因为冲突被解除了所有绑定的动作仍然可以重新绑定
+    fn rebind_slots(&self) -> Vec<(Action, f32)> {
+        let mut slots: Vec<(Action, f32)> = Vec::new();
+        for binding in Self::default().bindings.iter().chain(self.bindings.iter()) {
+            let slot = (binding.action, binding.scale.signum());
+            if !slots.contains(&slot) {
+                slots.push(slot);
+            }
+        }
+        slots
+    }
+}
+
+// 这一帧每个动作的值，由 InputMapPlugin 在 PreUpdate 中根据 InputMap 更新
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    values: HashMap<Action, f32>,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    // 轴的值，在 -1 到 1 之间
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+// 正在重新绑定的 (动作, 方向) 列表和当前位置
+#[derive(Resource, Debug, Default)]
+struct Rebinding {
+    slots: Vec<(Action, f32)>,
+    current: usize,
+}
+
+// 用户配置目录：XDG_CONFIG_HOME、HOME/.config 或 Windows 上的 APPDATA，都没有时不读写配置文件
+fn input_map_path() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let config_dir = env_dir("XDG_CONFIG_HOME")
+        .or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+        .or_else(|| env_dir("APPDATA"))?;
+    Some(config_dir.join(CONFIG_DIR_NAME).join(INPUT_MAP_FILE))
+}
+
+pub struct InputMapPlugin;
+
+// 动作状态在 Bevy 的输入系统之后更新，游戏系统在 Update 中读取 ActionState。
+// 按 F1 依次为每个动作的每个方向重新绑定键盘、鼠标或手柄按钮（Esc 跳过），全部完成后保存到配置文件
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputMap::load())
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, (rebind_actions, update_action_state).chain().after(InputSystem));
+    }
+}
+
+// 所有已连接手柄的按钮和摇杆，多个手柄同时作用于同一个玩家
+#[derive(SystemParam)]
+struct GamepadInputs<'w> {
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    button_axes: Res<'w, Axis<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl GamepadInputs<'_> {
+    // 按钮的模拟值（扳机），没有模拟值时按按下与否取 0 或 1，取所有手柄中最大的值
+    fn button(&self, button_type: GamepadButtonType) -> f32 {
+        self.gamepads
+            .iter()
+            .map(|gamepad| {
+                let button = GamepadButton::new(gamepad, button_type);
+                self.button_axes
+                    .get(button)
+                    .unwrap_or(self.buttons.pressed(button) as u8 as f32)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    // 摇杆的值，取所有手柄中绝对值最大的值
+    fn axis(&self, axis_type: GamepadAxisType) -> f32 {
+        self.gamepads
+            .iter()
+            .filter_map(|gamepad| self.axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .fold(0.0, |strongest: f32, value| if value.abs() > strongest.abs() { value } else { strongest })
+    }
+}
+
+fn update_action_state(
+    mut state: ResMut<ActionState>,
+    input_map: Res<InputMap>,
+    rebinding: Option<Res<Rebinding>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepads: GamepadInputs,
+) {
+    let state = state.as_mut();
+    state.values.clear();
+    state.just_pressed.clear();
+    // 重新绑定期间不触发任何动作
+    if rebinding.is_some() {
+        state.pressed.clear();
+        return;
+    }
+
+    for binding in input_map.bindings.iter() {
+        let value = match binding.source {
+            InputSource::Key(key) => keys.pressed(key) as u8 as f32,
+            InputSource::Mouse(button) => mouse_buttons.pressed(button) as u8 as f32,
+            InputSource::GamepadButton(button_type) => gamepads.button(button_type),
+            InputSource::GamepadAxis(axis_type) => gamepads.axis(axis_type),
+        };
+        *state.values.entry(binding.action).or_default() += value * binding.scale;
+    }
+
+    let mut pressed = HashSet::new();
+    for (action, value) in state.values.iter_mut() {
+        *value = value.clamp(-1.0, 1.0);
+        if *value > PRESS_THRESHOLD {
+            pressed.insert(*action);
+        }
+    }
+    state.just_pressed = pressed.difference(&state.pressed).copied().collect();
+    state.pressed = pressed;
+}
+
+// F1 开始重新绑定。每一步等待第一个按下的按键、鼠标按钮或手柄按钮，绑定到当前的动作方向上
+fn rebind_actions(
+    mut commands: Commands,
+    mut input_map: ResMut<InputMap>,
+    rebinding: Option<ResMut<Rebinding>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let Some(mut rebinding) = rebinding else {
+        if keys.just_pressed(REBIND_KEY) {
+            let slots = input_map.rebind_slots();
+            if let Some(&(action, scale)) = slots.first() {
+                info!("Rebinding controls, press an input for {action:?} ({scale:+}) or {SKIP_KEY:?} to skip");
+                commands.insert_resource(Rebinding { slots, current: 0 });
+            }
+        }
+        return;
+    };
+
+    let source = keys
+        .get_just_pressed()
+        .find(|key| **key != REBIND_KEY)
+        .map(|key| InputSource::Key(*key))
+        .or_else(|| mouse_buttons.get_just_pressed().next().map(|button| InputSource::Mouse(*button)))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| InputSource::GamepadButton(button.button_type))
+        });
+    let Some(source) = source else {
+        return;
+    };
+
+    let (action, scale) = rebinding.slots[rebinding.current];
+    if source != InputSource::Key(SKIP_KEY) {
+        input_map.rebind(action, scale, source);
+        info!("Bound {source:?} to {action:?} ({scale:+})");
+    }
+
+    rebinding.current += 1;
+    match rebinding.slots.get(rebinding.current) {
+        Some((action, scale)) => info!("Press an input for {action:?} ({scale:+}) or {SKIP_KEY:?} to skip"),
+        None => {
+            commands.remove_resource::<Rebinding>();
+            input_map.save();
+        }
+    }
+}
@@ -5,4 +5,26 @@ pub mod camera;
 pub mod asteroids;
 pub mod asset_loading;
 pub mod collision_detection;
-pub mod despawn;
\ No newline at end of file
+pub mod despawn;
+pub mod schedule;
+pub mod arena;
+pub mod spatial_hash;
+pub mod collider_shape;
+pub mod collision_response;
+pub mod continuous_collision;
+pub mod benchmark;
+pub mod ron_asset;
+pub mod waves;
+pub mod spawn_placement;
+pub mod rng;
+pub mod health;
+pub mod asteroid_mesh;
+pub mod archetypes;
+pub mod score;
+pub mod pickups;
+pub mod gravity;
+pub mod pool;
+pub mod weapons;
+pub mod player;
+pub mod input_map;
+pub mod ships;
@@ -1,9 +1,9 @@
-use bevy::{prelude::*, winit::accessibility};
+use bevy::prelude::*;
 
-use super::collision_detection::Collider;
+use super::{collision_detection::Collider, schedule::PhysicsSet};
 
 // 速度
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Default)]
 pub struct Velocity {
     pub value: Vec3,
 }
@@ -15,37 +15,111 @@ impl Velocity {
 }
 
 // 加速
-#[derive(Component, Debug)]
+//  - value:    持续的加速度（推力、小行星的初始加速度），由设置它的系统维护
+//  - external: 这一步中外力（引力）产生的加速度，由各个系统累加，积分后清零
+#[derive(Component, Debug, Default)]
 pub struct Acceleration {
     pub value: Vec3,
+    pub external: Vec3,
 }
 
 impl Acceleration {
     pub fn new(value: Vec3) -> Self {
+        Self { value, external: Vec3::ZERO }
+    }
+}
+
+// 角速度：方向为世界空间中的旋转轴，长度为旋转速率（弧度/秒）
+#[derive(Component, Debug, Default)]
+pub struct AngularVelocity {
+    pub value: Vec3,
+}
+
+impl AngularVelocity {
+    pub fn from_axis_angle(axis: Vec3, rate: f32) -> Self {
+        Self { value: axis.normalize_or_zero() * rate }
+    }
+}
+
+// 线性阻尼：每秒按 e^(-value) 的比例衰减速度，0 表示没有阻尼
+#[derive(Component, Debug, Default)]
+pub struct LinearDrag {
+    pub value: f32,
+}
+
+// 角阻尼：每秒按 e^(-value) 的比例衰减角速度
+#[derive(Component, Debug, Default)]
+pub struct AngularDrag {
+    pub value: f32,
+}
+
+// 速度上限：积分后速度的长度不会超过 value，默认不限速
+#[derive(Component, Debug)]
+pub struct MaxSpeed {
+    pub value: f32,
+}
+
+impl MaxSpeed {
+    pub fn new(value: f32) -> Self {
         Self { value }
     }
 }
 
-// 可移动物体 Bundle
-#[derive(Bundle)]
-pub struct MovingObjectBundle {
+impl Default for MaxSpeed {
+    fn default() -> Self {
+        Self { value: f32::INFINITY }
+    }
+}
+
+// 可移动物体的运动和碰撞组件，不包括模型。
+// 生成实体时和 SceneBundle 或 PbrBundle 组合使用，对象池复用实体时只需要重新插入这些组件。
+// 实现了 Default，创建时只需填写需要的字段，其余用 ..default() 补齐
+#[derive(Bundle, Default)]
+pub struct MotionBundle {
     pub velocity: Velocity,
     pub acceleration: Acceleration,
+    pub angular_velocity: AngularVelocity,
+    pub linear_drag: LinearDrag,
+    pub angular_drag: AngularDrag,
+    pub max_speed: MaxSpeed,
     pub collider: Collider,
-    pub model: SceneBundle,
 }
 
 pub struct MovementPlugin;
 
+// 速度和位置都在固定步长中积分：先由加速度更新速度，再由速度更新位置
+// 角速度同理：先施加角阻尼，再由角速度更新旋转
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_position);
+        app.add_systems(FixedUpdate, (
+            (update_velocity, update_angular_velocity).in_set(PhysicsSet::IntegrateAcceleration),
+            (update_position, update_rotation).in_set(PhysicsSet::IntegratePosition),
+        ));
     }
 }
 
-fn update_velocity(mut query: Query<(&Acceleration, &mut Velocity)>, time: Res<Time>) {
-    for (accleration, mut velocity) in query.iter_mut() {
-        velocity.value += accleration.value * time.delta_seconds();
+// 依次施加加速度（包括这一步累加的外力）、线性阻尼和速度上限
+pub fn update_velocity(
+    mut query: Query<(&mut Acceleration, &mut Velocity, Option<&LinearDrag>, Option<&MaxSpeed>)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (mut acceleration, mut velocity, drag, max_speed) in query.iter_mut() {
+        velocity.value += (acceleration.value + acceleration.external) * dt;
+        acceleration.external = Vec3::ZERO;
+        if let Some(drag) = drag {
+            velocity.value *= (-drag.value * dt).exp();
+        }
+        if let Some(max_speed) = max_speed {
+            velocity.value = velocity.value.clamp_length_max(max_speed.value);
+        }
+    }
+}
+
+fn update_angular_velocity(mut query: Query<(&mut AngularVelocity, &AngularDrag)>, time: Res<Time>) {
+    let dt = time.delta_seconds();
+    for (mut angular_velocity, drag) in query.iter_mut() {
+        angular_velocity.value *= (-drag.value * dt).exp();
     }
 }
 
@@ -53,4 +127,11 @@ fn update_position(mut query: Query<(&Velocity, &mut Transform)>, time: Res<Time
     for (velocity, mut transform) in query.iter_mut() {
         transform.translation += velocity.value * time.delta_seconds();
     }
+}
+
+// 角速度是世界空间中的旋转，所以左乘到当前旋转上
+fn update_rotation(mut query: Query<(&AngularVelocity, &mut Transform)>, time: Res<Time>) {
+    for (angular_velocity, mut transform) in query.iter_mut() {
+        transform.rotate(Quat::from_scaled_axis(angular_velocity.value * time.delta_seconds()));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,248 @@
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::*};
+
+use super::{
+    arena::ScreenWrap,
+    asteroids::Asteroid,
+    collision_detection::{Collider, CollisionLayers, CollisionStarted},
+    despawn::Lifetime,
+    health::{EntityDestroyed, HealthSet, Invulnerable},
+    movement::{AngularVelocity, MotionBundle, Velocity},
+    rng::{GameRng, RngStream},
+    schedule::PhysicsSet,
+    spaceship::Spaceship,
+};
+
+// 小行星被摧毁时掉落道具的概率
+const DROP_CHANCE: f64 = 0.15;
+const PICKUP_RADIUS: f32 = 1.5;
+const PICKUP_LIFETIME_SECONDS: f32 = 10.0;
+// 道具继承小行星速度的比例
+const PICKUP_INHERITED_VELOCITY: f32 = 0.3;
+const PICKUP_SPIN_RATE: f32 = 2.0;
+const EFFECT_DURATION_SECONDS: f32 = 10.0;
+
+// 道具种类。除了 ExtraLife 之外，其它道具都是有时限的效果
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pickup {
+    RapidFire,
+    Shield,
+    ExtraLife,
+    SpreadShot,
+}
+
+impl Pickup {
+    const ALL: [Pickup; 4] = [Pickup::RapidFire, Pickup::Shield, Pickup::ExtraLife, Pickup::SpreadShot];
+
+    // 掉落时随机选择的权重
+    fn weight(&self) -> f32 {
+        match self {
+            Pickup::RapidFire => 3.0,
+            Pickup::Shield => 2.0,
+            Pickup::ExtraLife => 0.5,
+            Pickup::SpreadShot => 2.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Pickup::RapidFire => Color::rgb(1.0, 0.8, 0.1),
+            Pickup::Shield => Color::rgb(0.2, 0.8, 1.0),
+            Pickup::ExtraLife => Color::rgb(0.2, 0.9, 0.3),
+            Pickup::SpreadShot => Color::rgb(0.9, 0.3, 0.9),
+        }
+    }
+
+    fn effect(&self) -> Option<Effect> {
+        match self {
+            Pickup::RapidFire => Some(Effect::RapidFire),
+            Pickup::Shield => Some(Effect::Shield),
+            Pickup::ExtraLife => None,
+            Pickup::SpreadShot => Some(Effect::SpreadShot),
+        }
+    }
+}
+
+// 有时限的效果。SpawnProtection 不是道具，而是飞船重生后的无敌时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    RapidFire,
+    Shield,
+    SpreadShot,
+    SpawnProtection,
+}
+
+// 实体身上正在生效的效果和各自的剩余时间
+#[derive(Component, Debug, Default)]
+pub struct ActiveEffects {
+    effects: Vec<(Effect, Timer)>,
+}
+
+impl ActiveEffects {
+    // 已经生效的效果重新计时，取剩余时间和新时长中较长的一个
+    pub fn add(&mut self, effect: Effect, seconds: f32) {
+        if let Some((_, timer)) = self.effects.iter_mut().find(|(active, _)| *active == effect) {
+            if timer.remaining_secs() < seconds {
+                *timer = Timer::from_seconds(seconds, TimerMode::Once);
+            }
+            return;
+        }
+        self.effects.push((effect, Timer::from_seconds(seconds, TimerMode::Once)));
+    }
+
+    pub fn is_active(&self, effect: Effect) -> bool {
+        self.effects.iter().any(|(active, _)| *active == effect)
+    }
+
+    fn tick(&mut self, delta: std::time::Duration) {
+        self.effects.retain_mut(|(_, timer)| !timer.tick(delta).finished());
+    }
+}
+
+// 飞船碰到道具时发送
+#[derive(Event, Debug)]
+pub struct PickupCollected {
+    pub pickup: Pickup,
+    pub collector: Entity,
+}
+
+// 道具的网格和每种道具的材质
+#[derive(Resource, Debug, Default)]
+struct PickupAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<(Pickup, Handle<StandardMaterial>)>,
+}
+
+pub struct PickupPlugin;
+
+// 掉落紧跟在伤害结算之后，拾取在碰撞响应阶段处理，效果的计时在每帧更新
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupAssets>()
+            .add_event::<PickupCollected>()
+            .add_systems(Startup, create_pickup_assets)
+            .add_systems(FixedUpdate, (
+                drop_pickups.in_set(HealthSet::Destroyed),
+                (collect_pickups, apply_pickups).chain().in_set(PhysicsSet::RespondCollisions),
+            ))
+            .add_systems(Update, (tick_active_effects, apply_shield_effect).chain());
+    }
+}
+
+fn create_pickup_assets(
+    mut pickup_assets: ResMut<PickupAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    pickup_assets.mesh = meshes.add(Mesh::from(shape::Cube { size: PICKUP_RADIUS * 1.2 }));
+    pickup_assets.materials = Pickup::ALL
+        .iter()
+        .map(|pickup| {
+            let material = materials.add(StandardMaterial {
+                base_color: pickup.color(),
+                emissive: pickup.color() * 0.5,
+                ..default()
+            });
+            (*pickup, material)
+        })
+        .collect();
+}
+
+// 被摧毁的小行星有一定概率在原地掉落一个道具，道具随小行星原来的方向缓慢漂移
+fn drop_pickups(
+    mut commands: Commands,
+    mut destroyed_events: EventReader<EntityDestroyed>,
+    asteroids: Query<(&Transform, &Velocity), With<Asteroid>>,
+    pickup_assets: Res<PickupAssets>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = game_rng.stream(RngStream::Loot);
+    for event in destroyed_events.read() {
+        let Ok((transform, velocity)) = asteroids.get(event.entity) else {
+            continue;
+        };
+        if !rng.gen_bool(DROP_CHANCE) {
+            continue;
+        }
+        let Ok(distribution) = WeightedIndex::new(Pickup::ALL.iter().map(Pickup::weight)) else {
+            continue;
+        };
+        let pickup = Pickup::ALL[distribution.sample(rng)];
+        let material = pickup_assets
+            .materials
+            .iter()
+            .find(|(kind, _)| *kind == pickup)
+            .map(|(_, material)| material.clone())
+            .unwrap_or_default();
+
+        commands.spawn((
+            MotionBundle {
+                velocity: Velocity::new(velocity.value * PICKUP_INHERITED_VELOCITY),
+                angular_velocity: AngularVelocity::from_axis_angle(Vec3::Y, PICKUP_SPIN_RATE),
+                collider: Collider::new(PICKUP_RADIUS).with_layers(CollisionLayers::PICKUP, CollisionLayers::PLAYER),
+                ..default()
+            },
+            PbrBundle {
+                mesh: pickup_assets.mesh.clone(),
+                material,
+                transform: Transform::from_translation(transform.translation),
+                ..default()
+            },
+            pickup,
+            Lifetime::from_seconds(PICKUP_LIFETIME_SECONDS),
+            ScreenWrap,
+        ));
+    }
+}
+
+// 飞船开始接触道具时拾取并销毁道具
+fn collect_pickups(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut collected_events: EventWriter<PickupCollected>,
+    pickups: Query<&Pickup>,
+    ships: Query<(), With<Spaceship>>,
+) {
+    for CollisionStarted(pair) in collision_events.read() {
+        for (entity, collector) in [(pair.entity_a, pair.entity_b), (pair.entity_b, pair.entity_a)] {
+            let Ok(pickup) = pickups.get(entity) else {
+                continue;
+            };
+            if ships.contains(collector) {
+                collected_events.send(PickupCollected { pickup: *pickup, collector });
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+// 有时限的道具给拾取者加上对应的效果。ExtraLife 没有效果，由管理生命数的系统读取 PickupCollected 事件处理
+fn apply_pickups(mut collected_events: EventReader<PickupCollected>, mut effects: Query<&mut ActiveEffects>) {
+    for event in collected_events.read() {
+        info!("Picked up {:?}", event.pickup);
+        let Some(effect) = event.pickup.effect() else {
+            continue;
+        };
+        if let Ok(mut active) = effects.get_mut(event.collector) {
+            active.add(effect, EFFECT_DURATION_SECONDS);
+        }
+    }
+}
+
+fn tick_active_effects(mut query: Query<&mut ActiveEffects>, time: Res<Time>) {
+    for mut effects in query.iter_mut() {
+        effects.tick(time.delta());
+    }
+}
+
+// 护盾道具和重生保护生效期间实体不会受到伤害
+fn apply_shield_effect(mut commands: Commands, query: Query<(Entity, &ActiveEffects, Has<Invulnerable>)>) {
+    for (entity, effects, invulnerable) in query.iter() {
+        let shielded = effects.is_active(Effect::Shield) || effects.is_active(Effect::SpawnProtection);
+        if shielded && !invulnerable {
+            commands.entity(entity).insert(Invulnerable);
+        } else if !shielded && invulnerable {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
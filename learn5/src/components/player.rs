@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use super::{
+    asset_loading::SceneAssets,
+    health::{EntityDestroyed, HealthSet, Invulnerable},
+    pickups::{ActiveEffects, Effect, Pickup, PickupCollected},
+    schedule::PhysicsSet,
+    ships::ShipTable,
+    spaceship::{spawn_ship, Spaceship},
+};
+
+const STARTING_LIVES: u32 = 3;
+// 飞船被摧毁后等待多久重生
+const RESPAWN_DELAY_SECONDS: f32 = 2.0;
+// 重生后的无敌时间和闪烁间隔
+const SPAWN_PROTECTION_SECONDS: f32 = 3.0;
+const BLINK_INTERVAL_SECONDS: f32 = 0.1;
+
+// 剩余的飞船数（包括场上的这一艘）
+#[derive(Resource, Debug)]
+pub struct Lives {
+    pub value: u32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self { value: STARTING_LIVES }
+    }
+}
+
+// 玩家的状态
+//  - Alive:      飞船在场上
+//  - Respawning: 飞船被摧毁，计时结束后在起始位置重生
+//  - GameOver:   没有剩余的飞船
+#[derive(Resource, Debug, Default)]
+pub enum PlayerState {
+    #[default]
+    Alive,
+    Respawning(Timer),
+    GameOver,
+}
+
+pub struct PlayerPlugin;
+
+// 飞船被摧毁紧跟在伤害结算之后处理，拾取的额外生命在碰撞响应之后计入，重生计时和闪烁在每帧更新
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lives>()
+            .init_resource::<PlayerState>()
+            .add_systems(FixedUpdate, (
+                handle_ship_destroyed.in_set(HealthSet::Destroyed),
+                collect_extra_lives.after(PhysicsSet::RespondCollisions),
+            ))
+            .add_systems(Update, (respawn_ship, blink_protected_ships));
+    }
+}
+
+// 飞船被摧毁时减少一条命，还有剩余时开始重生计时，否则游戏结束。
+// 飞船没有经过 EntityDestroyed 就从场上消失（被其它系统直接销毁）时同样处理，不会卡在 Alive 状态
+fn handle_ship_destroyed(
+    mut destroyed_events: EventReader<EntityDestroyed>,
+    ships: Query<(), With<Spaceship>>,
+    mut lives: ResMut<Lives>,
+    mut state: ResMut<PlayerState>,
+) {
+    let destroyed = destroyed_events.read().any(|event| ships.contains(event.entity));
+    let missing = matches!(*state, PlayerState::Alive) && ships.is_empty();
+    if !destroyed && !missing {
+        return;
+    }
+
+    lives.value = lives.value.saturating_sub(1);
+    if lives.value > 0 {
+        info!("Ship destroyed, lives left: {}", lives.value);
+        *state = PlayerState::Respawning(Timer::from_seconds(RESPAWN_DELAY_SECONDS, TimerMode::Once));
+    } else {
+        info!("Game over");
+        *state = PlayerState::GameOver;
+    }
+}
+
+// 拾取 ExtraLife 道具时增加一条命
+fn collect_extra_lives(mut collected_events: EventReader<PickupCollected>, mut lives: ResMut<Lives>) {
+    for event in collected_events.read() {
+        if event.pickup == Pickup::ExtraLife {
+            lives.value += 1;
+            info!("Lives: {}", lives.value);
+        }
+    }
+}
+
+// 重生的飞船带有重生保护效果。Invulnerable 直接插入，
+// 这样在 PickupPlugin 根据效果插入它之前的固定步长中飞船也不会受到伤害
+fn respawn_ship(
+    mut commands: Commands,
+    mut state: ResMut<PlayerState>,
+    scene_assets: Res<SceneAssets>,
+    ships: Res<ShipTable>,
+    time: Res<Time>,
+) {
+    if !matches!(*state, PlayerState::Respawning(_)) {
+        return;
+    }
+    let PlayerState::Respawning(timer) = state.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    let ship = spawn_ship(&mut commands, &ships, &scene_assets);
+    let mut effects = ActiveEffects::default();
+    effects.add(Effect::SpawnProtection, SPAWN_PROTECTION_SECONDS);
+    commands.entity(ship).insert((effects, Invulnerable));
+    *state = PlayerState::Alive;
+    info!("Ship respawned");
+}
+
+// 重生保护期间飞船闪烁，保护结束后恢复显示
+fn blink_protected_ships(mut query: Query<(&ActiveEffects, &mut Visibility), With<Spaceship>>, time: Res<Time>) {
+    let blink_off = (time.elapsed_seconds() / BLINK_INTERVAL_SECONDS) as u32 % 2 == 1;
+    for (effects, mut visibility) in query.iter_mut() {
+        let target = if effects.is_active(Effect::SpawnProtection) && blink_off {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+}
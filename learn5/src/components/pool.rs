@@ -0,0 +1,185 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::AppExit,
+    ecs::system::{EntityCommand, EntityCommands},
+    hierarchy::despawn_with_children_recursive,
+    prelude::*,
+};
+
+use super::{
+    collision_detection::{CollisionEnded, Contacts},
+    movement::MotionBundle,
+};
+
+// 可以放入对象池的实体种类，T 通常是这类实体的标记组件。
+// Active 是实体被回收时移除的组件：取出时插入的运动组件和其它组件都要包含在内，
+// 场景、网格、材质和 Transform 等会保留下来，下次取出时直接复用，不再重新生成场景
+pub trait Poolable: Component {
+    type Active: Bundle;
+}
+
+// 标记组件：实体在池中闲置，隐藏并且不参与任何游戏逻辑
+#[derive(Component, Debug)]
+pub struct Pooled;
+
+// 属于对象池的实体，release 把它放回对应的池中
+#[derive(Component)]
+pub struct PoolMember {
+    release: fn(&mut World, Entity),
+}
+
+impl PoolMember {
+    fn of<T: Poolable>() -> Self {
+        Self { release: release_to_pool::<T> }
+    }
+}
+
+// 命中：从池中取出闲置的实体；未命中：池为空，生成新的实体；回收：实体被放回池中
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PoolStats {
+    pub hits: u32,
+    pub misses: u32,
+    pub recycled: u32,
+}
+
+// 一种实体的对象池
+#[derive(Resource)]
+pub struct EntityPool<T: Poolable> {
+    free: Vec<Entity>,
+    stats: PoolStats,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Poolable> Default for EntityPool<T> {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            stats: PoolStats::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Poolable> EntityPool<T> {
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    // 预先生成 count 个隐藏的实例，model 生成每个实例的模型
+    pub fn warm<M: Bundle>(&mut self, commands: &mut Commands, count: usize, model: impl Fn() -> M) {
+        for _ in 0..count {
+            let entity = commands
+                .spawn(model())
+                .insert((Visibility::Hidden, Pooled, PoolMember::of::<T>()))
+                .id();
+            self.free.push(entity);
+        }
+    }
+
+    // 取出一个实体：池中有闲置实体时复用它和它的模型，只插入 Transform、运动组件和 active，
+    // 否则用 model 生成一个新的实体。model 可以是 SceneBundle、PbrBundle，
+    // 或者在之后另行插入网格时只用 SpatialBundle
+    pub fn spawn<M: Bundle>(
+        &mut self,
+        commands: &mut Commands,
+        transform: Transform,
+        motion: MotionBundle,
+        model: impl FnOnce() -> M,
+        active: impl Bundle,
+    ) -> Entity {
+        while let Some(entity) = self.free.pop() {
+            let Some(mut pooled) = commands.get_entity(entity) else {
+                continue;
+            };
+            pooled
+                .remove::<Pooled>()
+                .insert((transform, motion, Visibility::Inherited, active));
+            self.stats.hits += 1;
+            return entity;
+        }
+
+        self.stats.misses += 1;
+        commands
+            .spawn(model())
+            .insert((transform, motion, active, PoolMember::of::<T>()))
+            .id()
+    }
+}
+
+// 注册一种实体的对象池，退出游戏时打印它的统计数据，用来调整预热的数量
+pub struct PoolPlugin<T: Poolable> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Poolable> Default for PoolPlugin<T> {
+    fn default() -> Self {
+        Self { _marker: PhantomData }
+    }
+}
+
+impl<T: Poolable> Plugin for PoolPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityPool<T>>()
+            .add_systems(Last, report_pool_stats::<T>);
+    }
+}
+
+fn report_pool_stats<T: Poolable>(mut exit_events: EventReader<AppExit>, pool: Res<EntityPool<T>>) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    let stats = pool.stats();
+    info!(
+        "{} pool: {} hits, {} misses, {} recycled",
+        std::any::type_name::<T>().rsplit("::").next().unwrap_or_default(),
+        stats.hits, stats.misses, stats.recycled
+    );
+}
+
+fn release_to_pool<T: Poolable>(world: &mut World, entity: Entity) {
+    let Some(mut pooled) = world.get_entity_mut(entity) else {
+        return;
+    };
+    // 同一步中可能被多个系统回收，只放回池中一次
+    if pooled.contains::<Pooled>() {
+        return;
+    }
+    pooled.remove::<T::Active>().insert((Pooled, Visibility::Hidden));
+
+    // 实体可能在下一次碰撞检测之前就被重新取出，旧的接触要立即结束，否则新的接触不会触发 CollisionStarted
+    let ended = world
+        .get_resource_mut::<Contacts>()
+        .map(|mut contacts| contacts.remove_entity(entity))
+        .unwrap_or_default();
+    for pair in ended {
+        world.send_event(CollisionEnded(pair));
+    }
+
+    let mut pool = world.resource_mut::<EntityPool<T>>();
+    pool.free.push(entity);
+    pool.stats.recycled += 1;
+}
+
+// 属于对象池的实体放回池中，其它实体连同子实体一起销毁
+struct RecycleOrDespawn;
+
+impl EntityCommand for RecycleOrDespawn {
+    fn apply(self, entity: Entity, world: &mut World) {
+        match world.get::<PoolMember>(entity).map(|member| member.release) {
+            Some(release) => release(world, entity),
+            None => despawn_with_children_recursive(world, entity),
+        }
+    }
+}
+
+// 用 recycle_or_despawn() 代替 despawn_recursive()，对象池中的实体就会被回收而不是销毁
+pub trait RecycleCommandsExt {
+    fn recycle_or_despawn(&mut self);
+}
+
+impl RecycleCommandsExt for EntityCommands<'_, '_, '_> {
+    fn recycle_or_despawn(&mut self) {
+        self.add(RecycleOrDespawn);
+    }
+}
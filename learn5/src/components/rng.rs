@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+// 用于从同一个种子派生出互不相关的子随机流
+const STREAM_SEED_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// 随机子流。每个子系统使用自己的子流，
+// 这样某个子系统多用或少用一次随机数，不会改变其它子系统的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    Spawning,
+    Waves,
+    Debris,
+    Loot,
+    Meshes,
+}
+
+impl RngStream {
+    // 按声明顺序列出所有子流，GameRng 用 stream as usize 作为下标
+    const ALL: [RngStream; 5] = [
+        RngStream::Spawning,
+        RngStream::Waves,
+        RngStream::Debris,
+        RngStream::Loot,
+        RngStream::Meshes,
+    ];
+}
+
+// 编译期检查 ALL 的顺序和 stream as usize 一致
+const _: () = {
+    let mut index = 0;
+    while index < RngStream::ALL.len() {
+        assert!(RngStream::ALL[index] as usize == index);
+        index += 1;
+    }
+};
+
+// 游戏中所有的随机数都通过 GameRng 资源获取，相同的种子得到完全相同的一局游戏
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: [StdRng; RngStream::ALL.len()],
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = std::array::from_fn(|index| {
+            StdRng::seed_from_u64(seed ^ (index as u64 + 1).wrapping_mul(STREAM_SEED_MIX))
+        });
+        Self { seed, streams }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+// 解析命令行参数 --seed N
+pub fn seed_arg() -> Option<u64> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            return args.next().and_then(|value| value.parse().ok());
+        }
+    }
+    None
+}
+
+// seed 为 None 时随机选一个种子，并打印出来，便于之后用 --seed 复现
+pub struct GameRngPlugin {
+    pub seed: Option<u64>,
+}
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        app.insert_resource(GameRng::new(seed))
+            .add_systems(Startup, log_seed);
+    }
+}
+
+fn log_seed(game_rng: Res<GameRng>) {
+    let seed = game_rng.seed();
+    info!("Game seed: {seed} (rerun with --seed {seed} to reproduce)");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{input::InputPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::components::{
+        arena::ArenaPlugin,
+        asset_loading::SceneAssets,
+        asteroids::{Asteroid, AsteroidPlugin, AsteroidSize, SpawnAsteroid},
+        collision_detection::CollisionDetectionPlugin,
+        health::HealthPlugin,
+        movement::MovementPlugin,
+        schedule::SchedulePlugin,
+    };
+
+    const TICKS: u32 = 256;
+    const SPAWN_EVERY: u32 = 16;
+
+    // 无窗口地运行固定的帧数，每帧正好一个固定步长，返回所有小行星的位置
+    fn asteroid_positions(seed: u64) -> Vec<Vec3> {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .add_plugins(GameRngPlugin { seed: Some(seed) })
+            .add_plugins((SchedulePlugin, ArenaPlugin, MovementPlugin, CollisionDetectionPlugin, HealthPlugin, AsteroidPlugin))
+            .init_resource::<SceneAssets>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(15_625_000)));
+
+        for tick in 0..TICKS {
+            if tick % SPAWN_EVERY == 0 {
+                app.world.send_event(SpawnAsteroid { size: AsteroidSize::Large, speed: 8.0, strategy: None, archetype: None });
+            }
+            app.update();
+        }
+
+        let mut query = app.world.query_filtered::<&Transform, With<Asteroid>>();
+        query.iter(&app.world).map(|transform| transform.translation).collect()
+    }
+
+    #[test]
+    fn same_seed_produces_identical_asteroids() {
+        let first = asteroid_positions(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, asteroid_positions(7));
+        assert_ne!(first, asteroid_positions(8));
+    }
+}
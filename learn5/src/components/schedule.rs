@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+// 物理步长：每秒 64 次固定更新
+const FIXED_TIMESTEP_HZ: f64 = 64.0;
+
+// PhysicsSet 定义了固定步长调度 (FixedUpdate) 中各阶段的执行顺序：
+// 积分加速度 -> 积分位置 -> 环绕世界边界 -> 碰撞检测 -> 碰撞响应 -> 销毁实体
+// 这样每一帧的结果与帧率无关，并且可以重复。
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    IntegrateAcceleration,
+    IntegratePosition,
+    WrapPositions,
+    DetectCollisions,
+    RespondCollisions,
+    DespawnEntities,
+}
+
+pub struct SchedulePlugin;
+
+// 设置固定步长，并把 PhysicsSet 的各个阶段按顺序串联起来。
+// 在碰撞响应之后插入 apply_deferred，保证销毁阶段看不到响应阶段已经销毁的实体。
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::IntegrateAcceleration,
+                    PhysicsSet::IntegratePosition,
+                    PhysicsSet::WrapPositions,
+                    PhysicsSet::DetectCollisions,
+                    PhysicsSet::RespondCollisions,
+                    PhysicsSet::DespawnEntities,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                apply_deferred
+                    .after(PhysicsSet::RespondCollisions)
+                    .before(PhysicsSet::DespawnEntities),
+            );
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+use super::health::{EntityDestroyed, HealthSet};
+
+// 当前得分
+#[derive(Resource, Debug, Default)]
+pub struct Score {
+    pub value: u32,
+}
+
+// 被摧毁时得到的分数
+#[derive(Component, Debug)]
+pub struct ScoreValue(pub u32);
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_systems(FixedUpdate, award_score.in_set(HealthSet::Destroyed));
+    }
+}
+
+fn award_score(mut destroyed_events: EventReader<EntityDestroyed>, values: Query<&ScoreValue>, mut score: ResMut<Score>) {
+    for event in destroyed_events.read() {
+        if let Ok(ScoreValue(points)) = values.get(event.entity) {
+            score.value += points;
+        }
+    }
+}
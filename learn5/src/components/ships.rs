@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    input_map::{Action, ActionState},
+    ron_asset::RonAssetPlugin,
+    weapons::{Ammo, FireMode, WeaponDefinition, WeaponKind},
+};
+
+const SHIPS_PATH: &str = "spaceships.ships.ron";
+// 没有写 collider_radius、也不能使用场景碰撞形状的飞船使用的碰撞半径
+const DEFAULT_COLLIDER_RADIUS: f32 = 5.0;
+
+// 飞船的操控参数
+//  - speed:          街机模式下的速度
+//  - thrust:         惯性模式下的前进推力，retro_thrust 和 strafe_thrust 是反推和平移的推力
+//  - rotation_speed: 转向的角速度（弧度/秒），roll_speed 是滚转的角速度
+//  - dampener_drag:  惯性阻尼器开启时的阻力
+//  - max_speed:      惯性模式下的最大速度
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Handling {
+    pub speed: f32,
+    pub thrust: f32,
+    pub retro_thrust: f32,
+    pub strafe_thrust: f32,
+    pub rotation_speed: f32,
+    pub roll_speed: f32,
+    pub dampener_drag: f32,
+    pub max_speed: f32,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            speed: 25.0,
+            thrust: 30.0,
+            retro_thrust: 15.0,
+            strafe_thrust: 15.0,
+            rotation_speed: 2.5,
+            roll_speed: 2.5,
+            dampener_drag: 1.5,
+            max_speed: 40.0,
+        }
+    }
+}
+
+// 护盾：受到伤害 recharge_delay 秒后开始以每秒 recharge_rate 点恢复
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ShieldDefinition {
+    pub capacity: f32,
+    pub recharge_rate: f32,
+    pub recharge_delay: f32,
+}
+
+// 一种飞船的定义
+//  - model:           模型的资源路径，例如 "spaceship.glb#Scene0"
+//  - hull:            船体的生命值，护盾挡不住的伤害由船体承受
+//  - collider_radius: 可选，写了时使用这个半径的球形碰撞体；不写时默认的飞船模型使用由场景计算出的碰撞形状，
+//                     其它模型使用 DEFAULT_COLLIDER_RADIUS
+//  - ram_damage:      撞上小行星时对小行星造成的伤害
+//  - weapons:         武器组，按数字键选择武器的顺序，至少要有一件武器
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShipDefinition {
+    pub name: String,
+    pub model: String,
+    pub handling: Handling,
+    pub hull: f32,
+    pub shield: ShieldDefinition,
+    #[serde(default)]
+    pub collider_radius: Option<f32>,
+    pub mass: f32,
+    pub restitution: f32,
+    pub ram_damage: f32,
+    pub weapons: Vec<WeaponDefinition>,
+}
+
+impl ShipDefinition {
+    pub fn sphere_radius(&self) -> f32 {
+        self.collider_radius.unwrap_or(DEFAULT_COLLIDER_RADIUS)
+    }
+}
+
+// 从 assets/spaceships.ships.ron 加载的飞船列表
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct ShipDefinitions {
+    pub ships: Vec<ShipDefinition>,
+}
+
+// 可以选择的飞船和当前选中的飞船。配置文件加载（或热重载）完成后复制到这里，
+// 同时加载每种飞船的模型；加载完成之前只有默认的一种，只带导弹发射器
+#[derive(Resource, Debug)]
+pub struct ShipTable {
+    ships: Vec<ShipDefinition>,
+    models: Vec<Handle<Scene>>,
+    selected: usize,
+    handle: Handle<ShipDefinitions>,
+}
+
+impl Default for ShipTable {
+    fn default() -> Self {
+        Self {
+            ships: vec![ShipDefinition {
+                name: "Default".to_string(),
+                model: "spaceship.glb#Scene0".to_string(),
+                handling: Handling::default(),
+                hull: 1.0,
+                shield: ShieldDefinition {
+                    capacity: 30.0,
+                    recharge_rate: 5.0,
+                    recharge_delay: 3.0,
+                },
+                collider_radius: None,
+                mass: 5.0,
+                restitution: 0.3,
+                ram_damage: 2.0,
+                weapons: vec![WeaponDefinition {
+                    name: "Missile launcher".to_string(),
+                    kind: WeaponKind::Missile,
+                    mode: FireMode::Auto,
+                    ammo: Ammo::Unlimited,
+                    cooldown_seconds: 0.15,
+                    projectile_speed: 50.0,
+                    damage: 1.0,
+                }],
+            }],
+            models: Vec::new(),
+            selected: 0,
+            handle: Handle::default(),
+        }
+    }
+}
+
+impl ShipTable {
+    pub fn selected(&self) -> &ShipDefinition {
+        &self.ships[self.selected]
+    }
+
+    // 当前飞船的模型，模型还没有开始加载时是默认的空句柄
+    pub fn model(&self) -> Handle<Scene> {
+        self.models.get(self.selected).cloned().unwrap_or_default()
+    }
+
+    pub fn cycle(&mut self) {
+        self.selected = (self.selected + 1) % self.ships.len();
+    }
+
+    // 替换飞船列表并加载模型。重新加载后尽量保持选中同名的飞船，找不到时选中第一种
+    fn set_ships(&mut self, ships: Vec<ShipDefinition>, asset_server: &AssetServer) {
+        let current = &self.ships[self.selected].name;
+        self.selected = ships.iter().position(|ship| &ship.name == current).unwrap_or(0);
+        self.models = ships.iter().map(|ship| asset_server.load(&ship.model)).collect();
+        self.ships = ships;
+    }
+}
+
+pub struct ShipPlugin;
+
+// 启动时加载飞船列表，文件修改后自动重新加载。NextShip 切换到下一种飞船，
+// SpaceshipPlugin 把选中的飞船应用到场上的飞船，重生时也使用选中的飞船
+impl Plugin for ShipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<ShipDefinitions>::new(&["ships.ron"]))
+            .init_resource::<ShipTable>()
+            .add_systems(Startup, load_ships)
+            .add_systems(Update, (sync_ship_table, select_ship_controls));
+    }
+}
+
+fn load_ships(mut table: ResMut<ShipTable>, asset_server: Res<AssetServer>) {
+    table.handle = asset_server.load(SHIPS_PATH);
+    let ships = table.ships.clone();
+    table.set_ships(ships, &asset_server);
+}
+
+fn sync_ship_table(
+    mut table: ResMut<ShipTable>,
+    mut asset_events: EventReader<AssetEvent<ShipDefinitions>>,
+    assets: Res<Assets<ShipDefinitions>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in asset_events.read() {
+        if !event.is_loaded_with_dependencies(&table.handle) && !event.is_modified(&table.handle) {
+            continue;
+        }
+        let Some(loaded) = assets.get(&table.handle) else {
+            continue;
+        };
+        let ships: Vec<ShipDefinition> = loaded
+            .ships
+            .iter()
+            .filter(|ship| {
+                if ship.weapons.is_empty() {
+                    warn!("Ship {} has no weapons, skipping it", ship.name);
+                }
+                !ship.weapons.is_empty()
+            })
+            .cloned()
+            .collect();
+        if ships.is_empty() {
+            warn!("{SHIPS_PATH} has no usable ships, keeping the current table");
+            continue;
+        }
+        table.set_ships(ships, &asset_server);
+        info!("Loaded {} ships, selected {}", table.ships.len(), table.selected().name);
+    }
+}
+
+fn select_ship_controls(mut table: ResMut<ShipTable>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::NextShip) && table.ships.len() > 1 {
+        table.cycle();
+        info!("Ship: {}", table.selected().name);
+    }
+}
@@ -1,88 +1,287 @@
-use bevy::{ecs::query, input::keyboard::KeyboardInput, prelude::*};
-use super::{asset_loading::SceneAssets, collision_detection::Collider, movement::{Acceleration, MovingObjectBundle, Velocity}};
+use bevy::{ecs::{query::WorldQuery, system::SystemParam}, prelude::*};
+use super::{arena::{ClampToArena, DespawnOutsideArena, ScreenWrap}, asset_loading::{AutoColliderShape, SceneAssets, SceneKind}, collider_shape::ColliderShape, collision_detection::{Collider, CollisionLayers}, collision_response::{Mass, Restitution}, continuous_collision::FastMover, despawn::Lifetime, gravity::GravityScale, health::{DamageOnContact, Health, Shield}, input_map::{Action, ActionState}, pickups::{ActiveEffects, Effect}, movement::{Acceleration, LinearDrag, MaxSpeed, MotionBundle, Velocity}, pool::{EntityPool, PoolPlugin, Poolable}, ships::{Handling, ShipTable}, weapons::{update_weapons, Homing, LaserHit, Weapon, WeaponFired, WeaponKind, WeaponLoadout}};
 
 const STARTING_TRANSLATION: Vec3 = Vec3::new(0.0, 0.0, -20.0);
-const SPACESHIP_RADIUS: f32 = 5.0;
-const SPACESHIP_SPEED: f32 = 25.0;
-const SPACESHIP_ROTATION_SPEED: f32 = 2.5;
-const SPACESHIP_ROLL_SPEED: f32 = 2.5;
-const MISSILE_SPEED: f32 = 50.0;
 const MISSILE_FORWARD_SPAWN_SCALAR: f32 = 7.5;
 const MISSILE_RADIUS: f32 = 1.0;
+// 导弹只受一半的引力影响
+const MISSILE_GRAVITY_SCALE: f32 = 0.5;
+// 快速射击效果下冷却时间的缩放
+const RAPID_FIRE_COOLDOWN_SCALE: f32 = 0.4;
+// 散射效果下两侧导弹偏离机头方向的角度（弧度）
+const SPREAD_SHOT_ANGLE: f32 = 0.25;
+// 启动时预先生成的导弹数量
+const MISSILE_POOL_SIZE: usize = 32;
+const PELLET_RADIUS: f32 = 0.6;
+// 激光光束只显示很短的时间
+const LASER_BEAM_WIDTH: f32 = 0.3;
+const LASER_BEAM_SECONDS: f32 = 0.06;
+// 制导导弹的模型比普通导弹大一些
+const HOMING_MISSILE_SCALE: f32 = 1.5;
 
 // 这里定义了飞船的组件
 #[derive(Component, Debug)]
 pub struct Spaceship;
 
-// 这里定义了飞船导弹的组件
+// 这里定义了飞船导弹的组件（包括制导导弹）
 #[derive(Component, Debug)]
 pub struct SpaceshipMissile;
 
-// 定义了一个包含速度和模型的 Bundle
-// 方便在创建飞船实体时将这些组件一起添加
-#[derive(Bundle)]
-struct SpaceshipBundle {
-    velocity: Velocity,
-    model: SceneBundle,
+// 散射炮的弹丸
+#[derive(Component, Debug)]
+pub struct SpreadPellet;
+
+// 弹丸被回收时移除的组件，网格和材质保留下来
+impl Poolable for SpreadPellet {
+    type Active = (
+        MotionBundle,
+        SpreadPellet,
+        Health,
+        DamageOnContact,
+        FastMover,
+        DespawnOutsideArena,
+    );
+}
+
+// 弹丸和激光光束的网格和材质
+#[derive(Resource, Debug, Default)]
+struct WeaponAssets {
+    pellet_mesh: Handle<Mesh>,
+    pellet_material: Handle<StandardMaterial>,
+    beam_mesh: Handle<Mesh>,
+    beam_material: Handle<StandardMaterial>,
+}
+
+// 发射导弹、弹丸和激光需要的资源
+#[derive(SystemParam)]
+struct Projectiles<'w> {
+    scene_assets: Res<'w, SceneAssets>,
+    weapon_assets: Res<'w, WeaponAssets>,
+    missile_pool: ResMut<'w, EntityPool<SpaceshipMissile>>,
+    pellet_pool: ResMut<'w, EntityPool<SpreadPellet>>,
+}
+
+// 导弹被回收时移除的组件，场景和 Transform 保留下来
+impl Poolable for SpaceshipMissile {
+    type Active = (
+        MotionBundle,
+        SpaceshipMissile,
+        Health,
+        DamageOnContact,
+        FastMover,
+        GravityScale,
+        DespawnOutsideArena,
+        AutoColliderShape,
+        Homing,
+    );
 }
 
-// 注册了初始化飞船的系统和两个控制系统：一个用于飞船的运动控制，另一个用于飞船的武器控制
+// 飞行模式
+//  - Inertial: 牛顿式飞行，推力只改变加速度，松开按键后飞船依然保持惯性
+//  - Arcade:   原来的街机式飞行，每帧直接覆盖速度，松开按键立即停下
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlightMode {
+    #[default]
+    Inertial,
+    Arcade,
+}
+
+// 飞行参数，作为资源插入，可以在运行时调整
+// dampeners 为惯性阻尼器开关，开启时通过 LinearDrag 让飞船逐渐减速。
+// handling 是当前飞船的操控参数，选择飞船或飞船列表重新加载时由选中的飞船定义覆盖
+#[derive(Resource, Debug, Default)]
+pub struct FlightSettings {
+    pub mode: FlightMode,
+    pub dampeners: bool,
+    pub handling: Handling,
+}
+
+// 注册了初始化飞船、预热导弹对象池的系统，把选中的飞船定义应用到场上飞船的系统，
+// 三个控制系统：飞行模式切换、飞船的运动控制和飞船的武器控制，以及在武器射击时生成导弹、弹丸和激光的系统
 pub struct SpaceshipPlugin;
 
 impl Plugin for SpaceshipPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, spawn_spaceship)
-        .add_systems(Update, (spaceship_movement_controls, spaceship_weapon_controls));
+        app.init_resource::<FlightSettings>()
+        .add_plugins((PoolPlugin::<SpaceshipMissile>::default(), PoolPlugin::<SpreadPellet>::default()))
+        .init_resource::<WeaponAssets>()
+        .add_systems(Startup, create_weapon_assets)
+        .add_systems(PostStartup, (spawn_spaceship, warm_missile_pool))
+        .add_systems(Update, (
+            apply_selected_ship,
+            flight_settings_controls,
+            spaceship_movement_controls.after(flight_settings_controls),
+            spaceship_weapon_controls.before(update_weapons),
+            fire_spaceship_weapons.after(update_weapons),
+        ));
     }
 }
 
-// 这个系统在游戏启动后创建一个飞船实体，使用从 SceneAssets 资源加载的飞船模型，并设置其初始位置和速度
-fn spawn_spaceship(mut commands: Commands, scene_assets: Res<SceneAssets>) {
-    commands.spawn((
-        MovingObjectBundle {
-            velocity: Velocity::new(Vec3::ZERO),
-            acceleration: Acceleration::new(Vec3::ZERO),
-            collider: Collider::new(MISSILE_RADIUS),
-            model: SceneBundle {
-                scene: scene_assets.spaceship.clone(),
-                transform: Transform::from_translation(STARTING_TRANSLATION),
-                ..default()
-            },
+// 这个系统在游戏启动后创建一个飞船实体
+fn spawn_spaceship(mut commands: Commands, ships: Res<ShipTable>, scene_assets: Res<SceneAssets>) {
+    spawn_ship(&mut commands, &ships, &scene_assets);
+}
+
+// 在起始位置创建一个选中种类的飞船实体，护盾充满，武器组重新装填。
+// 重生时 PlayerPlugin 也使用这个函数
+pub fn spawn_ship(commands: &mut Commands, ships: &ShipTable, scene_assets: &SceneAssets) -> Entity {
+    let ship = ships.selected();
+    let model = ships.model();
+    let (loadout, weapon) = WeaponLoadout::new(ship.weapons.clone());
+    let entity = commands.spawn((
+        MotionBundle {
+            max_speed: MaxSpeed::new(ship.handling.max_speed),
+            collider: Collider::new(ship.sphere_radius())
+                .with_layers(CollisionLayers::PLAYER, CollisionLayers::ASTEROID | CollisionLayers::ENEMY | CollisionLayers::PICKUP),
+            ..default()
+        },
+        SceneBundle {
+            scene: model.clone(),
+            transform: Transform::from_translation(STARTING_TRANSLATION),
+            ..default()
         },
         Spaceship,
-    ));
+        ScreenWrap,
+        ClampToArena,
+        Mass::new(ship.mass),
+        Restitution::new(ship.restitution),
+        Health::new(ship.hull),
+        Shield::new(ship.shield.capacity, ship.shield.recharge_rate, ship.shield.recharge_delay),
+        DamageOnContact::new(ship.ram_damage, CollisionLayers::ASTEROID | CollisionLayers::ENEMY),
+        ActiveEffects::default(),
+        weapon,
+        loadout,
+    )).id();
+    if model == scene_assets.spaceship && ship.collider_radius.is_none() {
+        commands.entity(entity).insert(AutoColliderShape(SceneKind::Spaceship));
+    }
+    entity
 }
 
-// 这个系统处理飞船的运动，包括前进、后退和旋转。
-// 运动是根据按键输入（W, S, A, D, ShiftLeft, ControlLeft）进行的，
-// 使用 Bevy 的 Input<KeyCode> 来检测按键状态
-fn spaceship_movement_controls(
-    mut query: Query<(&mut Transform, &mut Velocity), With<Spaceship>>,
-    keyboard_input: Res<Input<KeyCode>>, 
-    time: Res<Time>) {
+// 飞船上由飞船定义决定的组件
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+struct ShipStats {
+    entity: Entity,
+    model: &'static mut Handle<Scene>,
+    collider: &'static mut Collider,
+    mass: &'static mut Mass,
+    restitution: &'static mut Restitution,
+    health: &'static mut Health,
+    shield: &'static mut Shield,
+    ram: &'static mut DamageOnContact,
+    weapon: &'static mut Weapon,
+    loadout: &'static mut WeaponLoadout,
+}
 
-    let (mut transform, mut velocity) = query.single_mut();
-    let mut rotation = 0.0;
-    let mut roll = 0.0;
-    let mut movement = 0.0;
+// 选择了另一种飞船，或者飞船列表重新加载后，更新飞行参数和场上飞船的模型、碰撞体和属性。
+// 船体和护盾保持原来的比例，武器组换成新的定义并重新装填
+fn apply_selected_ship(
+    mut commands: Commands,
+    mut ships: Query<ShipStats, With<Spaceship>>,
+    mut settings: ResMut<FlightSettings>,
+    table: Res<ShipTable>,
+    scene_assets: Res<SceneAssets>,
+) {
+    if !table.is_changed() {
+        return;
+    }
+    let ship = table.selected();
+    settings.handling = ship.handling;
 
-    if keyboard_input.pressed(KeyCode::D) {
-        rotation = -SPACESHIP_ROTATION_SPEED * time.delta_seconds();
-    } else if keyboard_input.pressed(KeyCode::A) {
-        rotation = SPACESHIP_ROTATION_SPEED * time.delta_seconds();
+    let Ok(mut stats) = ships.get_single_mut() else {
+        return;
+    };
+    // 替换 Handle<Scene> 会重新生成模型，所以只在模型改变时替换
+    let new_model = table.model();
+    if *stats.model != new_model {
+        *stats.model = new_model.clone();
+    }
+    // 定义中写了碰撞半径时使用球形碰撞体；没有写时默认模型使用由场景计算出的碰撞形状
+    if new_model == scene_assets.spaceship && ship.collider_radius.is_none() {
+        commands.entity(stats.entity).insert(AutoColliderShape(SceneKind::Spaceship));
+    } else {
+        commands.entity(stats.entity).remove::<AutoColliderShape>();
+        stats.collider.set_shape(ColliderShape::Sphere { radius: ship.sphere_radius() });
     }
 
-    if keyboard_input.pressed(KeyCode::S) {
-        movement = -SPACESHIP_SPEED;
-    } else if keyboard_input.pressed(KeyCode::W) {
-        movement = SPACESHIP_SPEED;
+    stats.mass.value = ship.mass;
+    stats.restitution.value = ship.restitution;
+    stats.health.current = rescale(stats.health.current, stats.health.max, ship.hull);
+    stats.health.max = ship.hull;
+    stats.shield.current = rescale(stats.shield.current, stats.shield.max, ship.shield.capacity);
+    stats.shield.max = ship.shield.capacity;
+    stats.shield.recharge_rate = ship.shield.recharge_rate;
+    stats.shield.recharge_delay = ship.shield.recharge_delay;
+    stats.ram.amount = ship.ram_damage;
+    (*stats.loadout, *stats.weapon) = WeaponLoadout::new(ship.weapons.clone());
+}
+
+// 上限从 old_max 变为 new_max 时保持 current 占上限的比例
+fn rescale(current: f32, old_max: f32, new_max: f32) -> f32 {
+    if old_max > 0.0 { current / old_max * new_max } else { new_max }
+}
+
+fn create_weapon_assets(
+    mut weapon_assets: ResMut<WeaponAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    weapon_assets.pellet_mesh = meshes.add(Mesh::from(shape::UVSphere { radius: PELLET_RADIUS, sectors: 12, stacks: 8 }));
+    weapon_assets.pellet_material = materials.add(StandardMaterial {
+        base_color: Color::rgb(1.0, 0.6, 0.1),
+        emissive: Color::rgb(1.0, 0.5, 0.0),
+        ..default()
+    });
+    weapon_assets.beam_mesh = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    weapon_assets.beam_material = materials.add(StandardMaterial {
+        base_color: Color::rgb(1.0, 0.1, 0.1),
+        emissive: Color::rgb(1.0, 0.0, 0.0),
+        unlit: true,
+        ..default()
+    });
+}
+
+// 预先生成一批隐藏的导弹，第一次开火时不需要再实例化导弹场景
+fn warm_missile_pool(mut commands: Commands, mut pool: ResMut<EntityPool<SpaceshipMissile>>, scene_assets: Res<SceneAssets>) {
+    let scene = scene_assets.missiles.clone();
+    pool.warm(&mut commands, MISSILE_POOL_SIZE, || SceneBundle { scene: scene.clone(), ..default() });
+}
+
+// ToggleFlightMode 在惯性和街机两种飞行模式之间切换，ToggleDampeners 开关惯性阻尼器
+fn flight_settings_controls(mut settings: ResMut<FlightSettings>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::ToggleFlightMode) {
+        settings.mode = match settings.mode {
+            FlightMode::Inertial => FlightMode::Arcade,
+            FlightMode::Arcade => FlightMode::Inertial,
+        };
+        info!("Flight mode: {:?}", settings.mode);
     }
 
-    if keyboard_input.pressed(KeyCode::ShiftLeft) {
-        roll = -SPACESHIP_ROLL_SPEED * time.delta_seconds();
-    } else if keyboard_input.pressed(KeyCode::ControlLeft) {
-        roll = SPACESHIP_ROLL_SPEED * time.delta_seconds();
+    if actions.just_pressed(Action::ToggleDampeners) {
+        settings.dampeners = !settings.dampeners;
+        info!("Inertial dampeners: {}", if settings.dampeners { "on" } else { "off" });
     }
+}
+
+// 这个系统处理飞船的运动，包括推进、反推、平移和旋转。
+// 运动由 ActionState 中的 Thrust、Turn、Strafe、Roll 四个轴驱动，
+// 手柄摇杆的模拟值可以平滑地控制转向速度和推力大小。
+// 惯性模式下推力写入 Acceleration，由 MovementPlugin 积分；街机模式下直接覆盖 Velocity。
+fn spaceship_movement_controls(
+    mut query: Query<(&mut Transform, &mut Velocity, &mut Acceleration, &mut LinearDrag, &mut MaxSpeed), With<Spaceship>>,
+    actions: Res<ActionState>,
+    settings: Res<FlightSettings>,
+    time: Res<Time>) {
+
+    // 飞船被摧毁后不再处理输入
+    let Ok((mut transform, mut velocity, mut acceleration, mut drag, mut max_speed)) = query.get_single_mut() else {
+        return;
+    };
+    let rotation = -actions.value(Action::Turn) * settings.handling.rotation_speed * time.delta_seconds();
+    let roll = actions.value(Action::Roll) * settings.handling.roll_speed * time.delta_seconds();
+    let movement = actions.value(Action::Thrust);
+    let strafe = actions.value(Action::Strafe);
 
     // Rotate around the Y-axis.
     // Ignores the Z-axis rotation applied below.
@@ -92,30 +291,176 @@ fn spaceship_movement_controls(
     // The rotation is relative to the current rotation!
     transform.rotate_local_z(roll);
 
-    // Update the spaceship's velocity based on new direction.
-    velocity.value = -transform.forward() * movement;
-}
-
-// 这个系统允许在按下空格键时发射导弹。导弹的初始位置和速度是基于飞船当前的朝向和位置计算的。
-fn spaceship_weapon_controls(mut commands: Commands, query: Query<&Transform, With<Spaceship>>, 
-    keyboard_input: Res<Input<KeyCode>>, scene_assets: Res<SceneAssets>) {
-
-    let transform = query.single();
-    if keyboard_input.pressed(KeyCode::Space) {
-        commands.spawn((
-            MovingObjectBundle {
-                velocity: Velocity::new(-transform.forward() * MISSILE_SPEED),
-                acceleration: Acceleration::new(Vec3::ZERO),
-                collider: Collider::new(SPACESHIP_RADIUS),
-                model: SceneBundle {
-                    scene: scene_assets.missiles.clone(),
-                    transform: Transform::from_translation(
-                        transform.translation + -transform.forward() * MISSILE_FORWARD_SPAWN_SCALAR,
-                    ),
-                    ..default()
-                },
-            },
-            SpaceshipMissile,
-        ));
+    // The model's nose points along -forward; strafing stays in the XZ plane.
+    let nose = -transform.forward();
+    let starboard = nose.cross(Vec3::Y).normalize_or_zero();
+
+    match settings.mode {
+        FlightMode::Inertial => {
+            let thrust = if movement > 0.0 { settings.handling.thrust } else { settings.handling.retro_thrust };
+            acceleration.value = nose * movement * thrust + starboard * strafe * settings.handling.strafe_thrust;
+            drag.value = if settings.dampeners { settings.handling.dampener_drag } else { 0.0 };
+            max_speed.value = settings.handling.max_speed;
+        }
+        FlightMode::Arcade => {
+            // Update the spaceship's velocity based on new direction.
+            acceleration.value = Vec3::ZERO;
+            drag.value = 0.0;
+            max_speed.value = settings.handling.speed;
+            velocity.value = (nose * movement + starboard * strafe) * settings.handling.speed;
+        }
+    }
+}
+
+// Fire 扣动扳机，SelectWeapon / NextWeapon / PreviousWeapon 切换武器，Reload 手动换弹。
+// 射击节奏由 WeaponPlugin 根据武器定义控制，快速射击效果缩短冷却时间
+fn spaceship_weapon_controls(
+    mut query: Query<(&mut Weapon, &mut WeaponLoadout, &ActiveEffects), With<Spaceship>>,
+    actions: Res<ActionState>,
+) {
+    let Ok((mut weapon, mut loadout, effects)) = query.get_single_mut() else {
+        return;
+    };
+    let mut switched = false;
+    for index in 0..loadout.weapon_count() {
+        if actions.just_pressed(Action::SelectWeapon(index)) {
+            switched |= loadout.select(index, &mut weapon);
+        }
+    }
+    if actions.just_pressed(Action::NextWeapon) {
+        switched |= loadout.cycle(1, &mut weapon);
+    }
+    if actions.just_pressed(Action::PreviousWeapon) {
+        switched |= loadout.cycle(-1, &mut weapon);
+    }
+    if switched {
+        info!("Weapon: {}", weapon.definition.name);
     }
-}
\ No newline at end of file
+
+    weapon.trigger = actions.pressed(Action::Fire);
+    weapon.cooldown_scale = if effects.is_active(Effect::RapidFire) { RAPID_FIRE_COOLDOWN_SCALE } else { 1.0 };
+    if actions.just_pressed(Action::Reload) {
+        weapon.reload();
+    }
+}
+
+// 飞船的武器每射出一发，就按武器种类发射导弹、弹丸或激光，伤害乘以这一发的威力。
+// 发射位置和方向是基于飞船当前的朝向和位置计算的。
+// 散射效果让只发射一发的武器额外向两侧各发射一发，散射炮本身的扇形不受影响
+fn fire_spaceship_weapons(
+    mut commands: Commands,
+    mut fired_events: EventReader<WeaponFired>,
+    ships: Query<(&Transform, &Weapon, &ActiveEffects), With<Spaceship>>,
+    mut projectiles: Projectiles,
+) {
+    for event in fired_events.read() {
+        let Ok((transform, weapon, effects)) = ships.get(event.entity) else {
+            continue;
+        };
+        let definition = &weapon.definition;
+        let damage = definition.damage * event.power;
+        let mut angles = match definition.kind {
+            WeaponKind::Spread { count, angle } => fan_angles(count, angle),
+            _ => vec![0.0],
+        };
+        if angles.len() == 1 && effects.is_active(Effect::SpreadShot) {
+            angles = vec![-SPREAD_SHOT_ANGLE, 0.0, SPREAD_SHOT_ANGLE];
+        }
+
+        for angle in angles {
+            let direction = Quat::from_rotation_y(angle) * -transform.forward();
+            let origin = transform.translation + direction * MISSILE_FORWARD_SPAWN_SCALAR;
+            let velocity = direction * definition.projectile_speed;
+            match definition.kind {
+                WeaponKind::Missile => {
+                    let missile = spawn_missile(&mut commands, &mut projectiles, origin, velocity, damage, 1.0);
+                    commands.entity(missile).insert(AutoColliderShape(SceneKind::Missiles));
+                }
+                WeaponKind::Homing { turn_rate } => {
+                    let missile = spawn_missile(&mut commands, &mut projectiles, origin, velocity, damage, HOMING_MISSILE_SCALE);
+                    commands.entity(missile).insert(Homing { turn_rate });
+                }
+                WeaponKind::Spread { .. } => {
+                    let mesh = projectiles.weapon_assets.pellet_mesh.clone();
+                    let material = projectiles.weapon_assets.pellet_material.clone();
+                    projectiles.pellet_pool.spawn(
+                        &mut commands,
+                        Transform::from_translation(origin),
+                        MotionBundle {
+                            velocity: Velocity::new(velocity),
+                            collider: Collider::new(PELLET_RADIUS)
+                                .with_layers(CollisionLayers::PLAYER_PROJECTILE, CollisionLayers::ASTEROID | CollisionLayers::ENEMY),
+                            ..default()
+                        },
+                        || PbrBundle { mesh, material, ..default() },
+                        (
+                            SpreadPellet,
+                            Health::new(1.0),
+                            DamageOnContact::new(damage, CollisionLayers::ASTEROID | CollisionLayers::ENEMY),
+                            FastMover,
+                            DespawnOutsideArena,
+                        ),
+                    );
+                }
+                WeaponKind::Laser { range } => {
+                    // 从飞船的位置发出，贴着机头的碰撞体也能被命中。
+                    // 命中和光束的长度在固定步长中确定，在那之前光束是隐藏的
+                    commands.spawn((
+                        PbrBundle {
+                            mesh: projectiles.weapon_assets.beam_mesh.clone(),
+                            material: projectiles.weapon_assets.beam_material.clone(),
+                            transform: Transform::from_translation(transform.translation)
+                                .looking_to(direction, Vec3::Y)
+                                .with_scale(Vec3::new(LASER_BEAM_WIDTH, LASER_BEAM_WIDTH, 1.0)),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        LaserHit::new(event.entity, transform.translation, direction, range, damage),
+                        Lifetime::from_seconds(LASER_BEAM_SECONDS),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// 在 angle 弧度的扇形内均匀分布的 count 个方向（相对机头的角度）
+fn fan_angles(count: u32, angle: f32) -> Vec<f32> {
+    if count <= 1 {
+        return vec![0.0];
+    }
+    (0..count)
+        .map(|index| angle * (index as f32 / (count - 1) as f32 - 0.5))
+        .collect()
+}
+
+// 从对象池中取出一枚导弹，scale 是模型和碰撞半径的缩放
+fn spawn_missile(
+    commands: &mut Commands,
+    projectiles: &mut Projectiles,
+    origin: Vec3,
+    velocity: Vec3,
+    damage: f32,
+    scale: f32,
+) -> Entity {
+    let scene = projectiles.scene_assets.missiles.clone();
+    projectiles.missile_pool.spawn(
+        commands,
+        Transform::from_translation(origin).with_scale(Vec3::splat(scale)),
+        MotionBundle {
+            velocity: Velocity::new(velocity),
+            collider: Collider::new(MISSILE_RADIUS * scale)
+                .with_layers(CollisionLayers::PLAYER_PROJECTILE, CollisionLayers::ASTEROID | CollisionLayers::ENEMY),
+            ..default()
+        },
+        || SceneBundle { scene, ..default() },
+        (
+            SpaceshipMissile,
+            Health::new(1.0),
+            DamageOnContact::new(damage, CollisionLayers::ASTEROID | CollisionLayers::ENEMY),
+            FastMover,
+            GravityScale(MISSILE_GRAVITY_SCALE),
+            DespawnOutsideArena,
+        ),
+    )
+}
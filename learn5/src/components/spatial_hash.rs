@@ -0,0 +1,96 @@
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+
+use super::arena::Arena;
+
+// 网格的最小边长，避免碰撞体都很小时格子过多
+const MIN_CELL_SIZE: f32 = 1.0;
+
+// SpatialHash 是碰撞检测的宽相位：一个位于 XZ 平面的均匀网格。
+// 每个物体按中心点放入一个格子，格子边长不小于最大碰撞体的直径，
+// 所以可能相交的两个物体一定位于相同或相邻（3x3）的格子中。
+// 环形世界中格子的坐标会按网格数取模，接缝两侧的格子也互为相邻。
+#[derive(Debug, Default)]
+pub struct SpatialHash {
+    cell_size: Vec2,
+    origin: Vec2,
+    wrap_cells: Option<IVec2>,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl SpatialHash {
+    // 清空网格并根据最大碰撞半径和世界模式重新设置格子大小，保留已分配的内存
+    pub fn reset(&mut self, max_radius: f32, arena: &Arena) {
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+
+        let cell_size = (max_radius * 2.0).max(MIN_CELL_SIZE);
+        if arena.is_wrapping() {
+            let size = arena.size();
+            let cells = IVec2::new(
+                ((size.x / cell_size).floor() as i32).max(1),
+                ((size.y / cell_size).floor() as i32).max(1),
+            );
+            // 网格刚好铺满整个区域，格子可能比 cell_size 稍大
+            self.cell_size = size / cells.as_vec2();
+            self.origin = -arena.half_extents;
+            self.wrap_cells = Some(cells);
+        } else {
+            self.cell_size = Vec2::splat(cell_size);
+            self.origin = Vec2::ZERO;
+            self.wrap_cells = None;
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec3) {
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    // 返回所有可能相交的 (i, j) 索引对，i < j，每一对只出现一次
+    pub fn candidate_pairs(&self, pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+        let mut neighbours: Vec<IVec2> = Vec::with_capacity(9);
+        for (&cell, bucket) in self.cells.iter() {
+            if bucket.is_empty() {
+                continue;
+            }
+
+            neighbours.clear();
+            for dz in -1..=1 {
+                for dx in -1..=1 {
+                    let neighbour = self.normalize(cell + IVec2::new(dx, dz));
+                    // 网格很小时取模后的格子可能重复
+                    if !neighbours.contains(&neighbour) {
+                        neighbours.push(neighbour);
+                    }
+                }
+            }
+
+            for neighbour in neighbours.iter() {
+                let Some(other) = self.cells.get(neighbour) else {
+                    continue;
+                };
+                for &i in bucket.iter() {
+                    for &j in other.iter() {
+                        if i < j {
+                            pairs.push((i, j));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn cell_of(&self, position: Vec3) -> IVec2 {
+        let local = (Vec2::new(position.x, position.z) - self.origin) / self.cell_size;
+        self.normalize(IVec2::new(local.x.floor() as i32, local.y.floor() as i32))
+    }
+
+    fn normalize(&self, cell: IVec2) -> IVec2 {
+        match self.wrap_cells {
+            Some(cells) => IVec2::new(cell.x.rem_euclid(cells.x), cell.y.rem_euclid(cells.y)),
+            None => cell,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use super::arena::Arena;
+
+// 与飞船之间的最小间隙，在此范围内不会生成新的实体
+const SHIP_CLEARANCE: f32 = 20.0;
+// 与其它碰撞体之间的最小间隙
+const COLLIDER_CLEARANCE: f32 = 2.0;
+const MAX_ATTEMPTS: u32 = 16;
+// 边缘生成时，朝向区域中心的方向两侧随机偏转的最大角度（弧度）
+const INWARD_SPREAD_ANGLE: f32 = 0.6;
+
+// 生成位置的选取方式
+//  - Field:    在整个区域内随机选点，方向随机
+//  - EdgeRing: 在区域边缘一圈上随机选点，大致朝向区域中心飞入
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum SpawnStrategy {
+    Field,
+    #[default]
+    EdgeRing,
+}
+
+// 生成位置的配置，作为资源插入。波次配置中可以为某一波单独指定 strategy
+// 候选点离飞船或其它碰撞体太近时重新采样，最多尝试 max_attempts 次，
+// 全部失败时使用间隙最大的那个候选点
+#[derive(Resource, Debug)]
+pub struct SpawnPlacement {
+    pub strategy: SpawnStrategy,
+    pub ship_clearance: f32,
+    pub collider_clearance: f32,
+    pub max_attempts: u32,
+    pub inward_spread: f32,
+}
+
+impl Default for SpawnPlacement {
+    fn default() -> Self {
+        Self {
+            strategy: SpawnStrategy::default(),
+            ship_clearance: SHIP_CLEARANCE,
+            collider_clearance: COLLIDER_CLEARANCE,
+            max_attempts: MAX_ATTEMPTS,
+            inward_spread: INWARD_SPREAD_ANGLE,
+        }
+    }
+}
+
+// 需要避开的区域：中心和半径（已包含所需的间隙）
+#[derive(Debug, Clone, Copy)]
+pub struct Obstacle {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+// 选出的生成位置和初始方向（XZ 平面上的单位向量）
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnPoint {
+    pub position: Vec3,
+    pub heading: Vec3,
+}
+
+impl SpawnPlacement {
+    // 用拒绝采样为半径为 radius 的实体找一个不与 obstacles 重叠的生成位置
+    pub fn find(
+        &self,
+        strategy: SpawnStrategy,
+        arena: &Arena,
+        radius: f32,
+        obstacles: &[Obstacle],
+        rng: &mut impl Rng,
+    ) -> SpawnPoint {
+        let mut best: Option<(f32, SpawnPoint)> = None;
+        for _ in 0..self.max_attempts.max(1) {
+            let candidate = self.sample(strategy, arena, radius, rng);
+            // 最小间隙，小于 0 表示与某个障碍重叠
+            let margin = obstacles
+                .iter()
+                .map(|obstacle| arena.offset(obstacle.position, candidate.position).length() - obstacle.radius - radius)
+                .fold(f32::INFINITY, f32::min);
+            if margin >= 0.0 {
+                return candidate;
+            }
+            if best.map_or(true, |(best_margin, _)| margin > best_margin) {
+                best = Some((margin, candidate));
+            }
+        }
+        let (_, point) = best.expect("at least one spawn attempt");
+        debug!("No clear spawn point after {} attempts, using the best candidate", self.max_attempts);
+        point
+    }
+
+    fn sample(&self, strategy: SpawnStrategy, arena: &Arena, radius: f32, rng: &mut impl Rng) -> SpawnPoint {
+        let half = (arena.half_extents - Vec2::splat(radius)).max(Vec2::ZERO);
+        match strategy {
+            SpawnStrategy::Field => {
+                let position = Vec3::new(random_in(rng, half.x), 0.0, random_in(rng, half.y));
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                SpawnPoint { position, heading: Quat::from_rotation_y(angle) * Vec3::X }
+            }
+            SpawnStrategy::EdgeRing => {
+                // 按边长选一条边，再在这条边上均匀选点，相当于沿矩形周长均匀分布
+                let total = half.x + half.y;
+                let along_x = if total > 0.0 { half.x / total } else { 0.5 };
+                let side = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                let position = if rng.gen_bool(along_x as f64) {
+                    Vec3::new(random_in(rng, half.x), 0.0, side * half.y)
+                } else {
+                    Vec3::new(side * half.x, 0.0, random_in(rng, half.y))
+                };
+                let inward = (-position).try_normalize().unwrap_or(Vec3::X);
+                let angle = rng.gen_range(-self.inward_spread..=self.inward_spread);
+                SpawnPoint { position, heading: Quat::from_rotation_y(angle) * inward }
+            }
+        }
+    }
+}
+
+fn random_in(rng: &mut impl Rng, half_extent: f32) -> f32 {
+    if half_extent <= 0.0 {
+        return 0.0;
+    }
+    rng.gen_range(-half_extent..half_extent)
+}
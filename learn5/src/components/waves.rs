@@ -0,0 +1,253 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    archetypes::ArchetypeTable,
+    asteroids::{Asteroid, AsteroidSize, SpawnAsteroid},
+    ron_asset::RonAssetPlugin,
+    rng::{GameRng, RngStream},
+    spawn_placement::SpawnStrategy,
+};
+
+const WAVE_TABLE_PATH: &str = "asteroids.waves.ron";
+const BREATHER_SECONDS: f32 = 3.0;
+const SPAWN_INTERVAL_SECONDS: f32 = 1.0;
+
+// 每一波中各个大小等级出现的权重，不需要归一化
+#[derive(Debug, Clone, Deserialize)]
+pub struct SizeMix {
+    pub large: f32,
+    pub medium: f32,
+    pub small: f32,
+}
+
+impl SizeMix {
+    pub fn choose(&self, rng: &mut impl Rng) -> AsteroidSize {
+        let weights = [self.large.max(0.0), self.medium.max(0.0), self.small.max(0.0)];
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return AsteroidSize::Large;
+        }
+        let mut pick = rng.gen_range(0.0..total);
+        for (size, weight) in [AsteroidSize::Large, AsteroidSize::Medium, AsteroidSize::Small].into_iter().zip(weights) {
+            if pick < weight {
+                return size;
+            }
+            pick -= weight;
+        }
+        AsteroidSize::Small
+    }
+}
+
+// 一波的内容：小行星的数量（预算）、初始速度和大小组成
+// strategy 可选，不填时使用 SpawnPlacement 资源中的默认生成方式
+// archetypes 是小行星种类的权重表 [(名称, 权重)]，由 ArchetypeTable::choose 选择，不填时按各种类自己的权重选择
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDefinition {
+    pub count: u32,
+    pub speed: f32,
+    pub size_mix: SizeMix,
+    #[serde(default)]
+    pub strategy: Option<SpawnStrategy>,
+    #[serde(default)]
+    pub archetypes: Vec<(String, f32)>,
+}
+
+// 超过配置文件中列出的波数之后，每多一波在最后一波的基础上增加的难度
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveScaling {
+    pub count_per_wave: u32,
+    pub speed_per_wave: f32,
+    pub large_weight_per_wave: f32,
+    pub max_count: u32,
+    pub max_speed: f32,
+}
+
+// 波次配置，从 assets/asteroids.waves.ron 加载
+//  - breather_seconds:       两波之间的休息时间
+//  - spawn_interval_seconds: 同一波中相邻两颗小行星的生成间隔
+//  - waves:                  前几波的具体内容，第 1 波对应 waves[0]
+//  - scaling:                列表用完之后的难度递增规则
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct WaveTable {
+    pub breather_seconds: f32,
+    pub spawn_interval_seconds: f32,
+    pub waves: Vec<WaveDefinition>,
+    pub scaling: WaveScaling,
+}
+
+impl Default for WaveTable {
+    fn default() -> Self {
+        Self {
+            breather_seconds: BREATHER_SECONDS,
+            spawn_interval_seconds: SPAWN_INTERVAL_SECONDS,
+            waves: vec![WaveDefinition {
+                count: 5,
+                speed: 5.0,
+                size_mix: SizeMix { large: 1.0, medium: 0.0, small: 0.0 },
+                strategy: None,
+                archetypes: Vec::new(),
+            }],
+            scaling: WaveScaling {
+                count_per_wave: 2,
+                speed_per_wave: 0.5,
+                large_weight_per_wave: 0.0,
+                max_count: 30,
+                max_speed: 12.0,
+            },
+        }
+    }
+}
+
+impl WaveTable {
+    // 第 number 波（从 1 开始）的内容
+    pub fn wave(&self, number: u32) -> WaveDefinition {
+        let index = number.saturating_sub(1) as usize;
+        if let Some(wave) = self.waves.get(index) {
+            return wave.clone();
+        }
+
+        let Some(last) = self.waves.last() else {
+            return WaveTable::default().wave(number);
+        };
+        let extra = (index + 1 - self.waves.len()) as u32;
+        let scaling = &self.scaling;
+        let mut size_mix = last.size_mix.clone();
+        size_mix.large += scaling.large_weight_per_wave * extra as f32;
+        WaveDefinition {
+            count: (last.count + scaling.count_per_wave * extra).min(scaling.max_count.max(last.count)),
+            speed: (last.speed + scaling.speed_per_wave * extra as f32).min(scaling.max_speed.max(last.speed)),
+            size_mix,
+            strategy: last.strategy,
+            archetypes: last.archetypes.clone(),
+        }
+    }
+}
+
+// 新的一波开始生成
+#[derive(Event, Debug)]
+pub struct WaveStarted {
+    pub wave: u32,
+    pub asteroid_count: u32,
+}
+
+// 一波的小行星（包括分裂出的碎片）全部被清除
+#[derive(Event, Debug)]
+pub struct WaveCleared {
+    pub wave: u32,
+}
+
+// 导演的状态：休息 -> 按间隔生成这一波的小行星 -> 等待全部清除 -> 休息 ...
+#[derive(Debug)]
+enum WavePhase {
+    Breather(Timer),
+    Spawning { wave: WaveDefinition, remaining: u32, timer: Timer },
+    Clearing,
+}
+
+// 波次导演，替代原来固定间隔无限生成的 SpawnTimer
+// 配置文件尚未加载完成（或者加载失败）时使用 WaveTable::default()
+#[derive(Resource, Debug)]
+pub struct WaveDirector {
+    pub wave: u32,
+    phase: WavePhase,
+    table: Handle<WaveTable>,
+    fallback: WaveTable,
+}
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<WaveTable>::new(&["waves.ron"]))
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_systems(Startup, load_wave_table)
+            .add_systems(Update, (run_wave_director, announce_waves).chain());
+    }
+}
+
+fn load_wave_table(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let fallback = WaveTable::default();
+    commands.insert_resource(WaveDirector {
+        wave: 0,
+        phase: WavePhase::Breather(Timer::from_seconds(fallback.breather_seconds, TimerMode::Once)),
+        table: asset_server.load(WAVE_TABLE_PATH),
+        fallback,
+    });
+}
+
+// 导演发出的生成请求和波次事件
+#[derive(SystemParam)]
+pub struct DirectorEvents<'w> {
+    spawn: EventWriter<'w, SpawnAsteroid>,
+    started: EventWriter<'w, WaveStarted>,
+    cleared: EventWriter<'w, WaveCleared>,
+}
+
+// 推进导演的状态机。生成请求以 SpawnAsteroid 事件发给 AsteroidPlugin，
+// 由它决定小行星的位置和运动方向
+pub fn run_wave_director(
+    mut director: ResMut<WaveDirector>,
+    tables: Res<Assets<WaveTable>>,
+    archetypes: Res<ArchetypeTable>,
+    asteroids: Query<(), With<Asteroid>>,
+    mut events: DirectorEvents,
+    mut game_rng: ResMut<GameRng>,
+    time: Res<Time>,
+) {
+    // 分别借用导演的各个字段：波次表只读，状态机可变，不需要复制整张波次表
+    let WaveDirector { wave: number, phase, table, fallback } = director.as_mut();
+    let table = tables.get(&*table).unwrap_or(&*fallback);
+
+    match phase {
+        WavePhase::Breather(timer) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+            let wave = table.wave(*number + 1);
+            events.started.send(WaveStarted { wave: *number + 1, asteroid_count: wave.count });
+            *number += 1;
+            *phase = WavePhase::Spawning {
+                remaining: wave.count,
+                wave,
+                timer: Timer::from_seconds(table.spawn_interval_seconds, TimerMode::Repeating),
+            };
+        }
+        WavePhase::Spawning { wave, remaining, timer } => {
+            timer.tick(time.delta());
+            let rng = game_rng.stream(RngStream::Waves);
+            for _ in 0..timer.times_finished_this_tick().min(*remaining) {
+                events.spawn.send(SpawnAsteroid {
+                    size: wave.size_mix.choose(rng),
+                    speed: wave.speed,
+                    strategy: wave.strategy,
+                    archetype: (!wave.archetypes.is_empty())
+                        .then(|| archetypes.get(archetypes.choose(&wave.archetypes, rng)).name.clone()),
+                });
+                *remaining -= 1;
+            }
+            // 最后一颗小行星要到这一帧的命令执行后才存在，下一帧再检查是否清除
+            if *remaining == 0 {
+                *phase = WavePhase::Clearing;
+            }
+        }
+        WavePhase::Clearing => {
+            if !asteroids.is_empty() {
+                return;
+            }
+            events.cleared.send(WaveCleared { wave: *number });
+            *phase = WavePhase::Breather(Timer::from_seconds(table.breather_seconds, TimerMode::Once));
+        }
+    }
+}
+
+fn announce_waves(mut started_events: EventReader<WaveStarted>, mut cleared_events: EventReader<WaveCleared>) {
+    for event in started_events.read() {
+        info!("Wave {} started: {} asteroids", event.wave, event.asteroid_count);
+    }
+    for event in cleared_events.read() {
+        info!("Wave {} cleared", event.wave);
+    }
+}
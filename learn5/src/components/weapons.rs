@@ -0,0 +1,490 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    arena::Arena,
+    asteroids::Asteroid,
+    collision_detection::{Collider, CollisionLayers},
+    continuous_collision::raycast,
+    health::{DamageDealt, HealthSet},
+    movement::Velocity,
+    schedule::PhysicsSet,
+};
+
+// 热量达到这个值时武器过热
+const OVERHEAT: f32 = 1.0;
+// 冷却时间和连发间隔的下限，避免一帧中射出无限多发
+const MIN_SHOT_INTERVAL: f32 = 0.01;
+
+// 射击模式
+//  - Auto:   按住扳机时按冷却时间连续射击
+//  - Burst:  扣一次扳机连续射出 count 发，每发间隔 interval 秒，打完最后一发后开始冷却
+//  - Charge: 按住扳机蓄力，松开时射出一发。蓄力不足 min_seconds 时不会射出，
+//            威力随蓄力时间从 1 线性增加，到 max_seconds 时达到 max_power
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum FireMode {
+    Auto,
+    Burst { count: u32, interval: f32 },
+    Charge { min_seconds: f32, max_seconds: f32, max_power: f32 },
+}
+
+// 弹药
+//  - Unlimited: 不限弹药
+//  - Magazine:  每发消耗一颗，弹匣打空后自动换弹，换弹期间不能射击
+//  - Heat:      每发增加 per_shot 的热量，每秒散热 cooling。热量达到 1 时过热，降到 recover 以下才能继续射击
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Ammo {
+    Unlimited,
+    Magazine { size: u32, reload_seconds: f32 },
+    Heat { per_shot: f32, cooling: f32, recover: f32 },
+}
+
+// 武器种类
+//  - Missile: 沿机头方向发射一枚直线飞行的导弹
+//  - Spread:  在机头前方 angle 弧度的扇形内均匀发射 count 枚弹丸
+//  - Laser:   即时命中的激光，对射线上 range 距离内第一个碰撞体造成伤害
+//  - Homing:  制导导弹，以不超过 turn_rate（弧度/秒）的速率转向最近的小行星
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum WeaponKind {
+    Missile,
+    Spread { count: u32, angle: f32 },
+    Laser { range: f32 },
+    Homing { turn_rate: f32 },
+}
+
+// 一种武器的定义。cooldown_seconds 是两次射击（连发模式下是两轮连发）之间的最短间隔
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeaponDefinition {
+    pub name: String,
+    pub kind: WeaponKind,
+    pub mode: FireMode,
+    pub ammo: Ammo,
+    pub cooldown_seconds: f32,
+    pub projectile_speed: f32,
+    pub damage: f32,
+}
+
+// 武器和它的状态。控制系统每帧设置 trigger（扳机是否按下）和 cooldown_scale（冷却时间的缩放），
+// WeaponPlugin 据此推进冷却、弹药和射击模式，每射出一发发送一个 WeaponFired 事件
+#[derive(Component, Debug)]
+pub struct Weapon {
+    pub definition: WeaponDefinition,
+    pub trigger: bool,
+    pub cooldown_scale: f32,
+    cooldown: f32,
+    rounds: u32,
+    reloading: f32,
+    heat: f32,
+    overheated: bool,
+    burst_remaining: u32,
+    burst_timer: f32,
+    charge: Option<f32>,
+}
+
+impl Weapon {
+    pub fn new(definition: WeaponDefinition) -> Self {
+        let rounds = match definition.ammo {
+            Ammo::Magazine { size, .. } => size,
+            _ => 0,
+        };
+        Self {
+            definition,
+            trigger: false,
+            cooldown_scale: 1.0,
+            cooldown: 0.0,
+            rounds,
+            reloading: 0.0,
+            heat: 0.0,
+            overheated: false,
+            burst_remaining: 0,
+            burst_timer: 0.0,
+            charge: None,
+        }
+    }
+
+    // 手动换弹，只对没有装满的弹匣有效
+    pub fn reload(&mut self) {
+        if let Ammo::Magazine { size, reload_seconds } = self.definition.ammo {
+            if self.reloading <= 0.0 && self.rounds < size {
+                self.reloading = reload_seconds;
+                info!("{}: reloading", self.definition.name);
+            }
+        }
+    }
+
+    // 收起武器时松开扳机，放弃正在进行的连发和蓄力
+    fn holster(&mut self) {
+        self.trigger = false;
+        self.burst_remaining = 0;
+        self.charge = None;
+    }
+
+    // 弹药是否允许射击
+    fn ready(&self) -> bool {
+        match self.definition.ammo {
+            Ammo::Unlimited => true,
+            Ammo::Magazine { .. } => self.reloading <= 0.0 && self.rounds > 0,
+            Ammo::Heat { .. } => !self.overheated,
+        }
+    }
+
+    fn scaled_cooldown(&self) -> f32 {
+        (self.definition.cooldown_seconds * self.cooldown_scale).max(MIN_SHOT_INTERVAL)
+    }
+
+    // 换弹计时和散热
+    fn tick_ammo(&mut self, dt: f32) {
+        match self.definition.ammo {
+            Ammo::Unlimited => {}
+            Ammo::Magazine { size, .. } => {
+                if self.reloading > 0.0 {
+                    self.reloading -= dt;
+                    if self.reloading <= 0.0 {
+                        self.reloading = 0.0;
+                        self.rounds = size;
+                    }
+                }
+            }
+            Ammo::Heat { cooling, recover, .. } => {
+                self.heat = (self.heat - cooling * dt).max(0.0);
+                if self.overheated && self.heat <= recover {
+                    self.overheated = false;
+                }
+            }
+        }
+    }
+
+    // 消耗弹药，返回这一发的威力
+    fn shoot(&mut self, power: f32) -> f32 {
+        match self.definition.ammo {
+            Ammo::Unlimited => {}
+            Ammo::Magazine { reload_seconds, .. } => {
+                self.rounds = self.rounds.saturating_sub(1);
+                if self.rounds == 0 {
+                    self.reloading = reload_seconds;
+                    info!("{}: magazine empty, reloading", self.definition.name);
+                }
+            }
+            Ammo::Heat { per_shot, .. } => {
+                self.heat += per_shot;
+                if self.heat >= OVERHEAT {
+                    self.overheated = true;
+                    info!("{}: overheated", self.definition.name);
+                }
+            }
+        }
+        power
+    }
+
+    // 推进 dt 秒，返回这段时间内射出的每一发的威力。
+    // 射击后冷却时间加到剩余的冷却上而不是直接覆盖，上一帧多出的时间留给下一发，
+    // 所以射速与帧率无关；一帧比冷却时间长时这一帧会射出多发。
+    // 停止射击时剩余的冷却不会变成负数，松开扳机不能积攒射击次数
+    fn update(&mut self, dt: f32) -> Vec<f32> {
+        self.cooldown -= dt;
+        self.tick_ammo(dt);
+
+        let mut shots = Vec::new();
+        match self.definition.mode {
+            FireMode::Auto => {
+                while self.trigger && self.cooldown <= 0.0 && self.ready() {
+                    shots.push(self.shoot(1.0));
+                    self.cooldown += self.scaled_cooldown();
+                }
+            }
+            FireMode::Burst { count, interval } => {
+                if self.burst_remaining > 0 {
+                    self.burst_timer -= dt;
+                }
+                loop {
+                    if self.burst_remaining == 0 {
+                        if !self.trigger || self.cooldown > 0.0 || !self.ready() {
+                            break;
+                        }
+                        self.burst_remaining = count.max(1);
+                        self.burst_timer = self.cooldown;
+                    }
+                    if self.burst_timer > 0.0 {
+                        break;
+                    }
+                    // 弹匣打空或过热时连发中断
+                    if !self.ready() {
+                        self.burst_remaining = 0;
+                        self.cooldown = self.burst_timer + self.scaled_cooldown();
+                        break;
+                    }
+                    self.burst_remaining -= 1;
+                    shots.push(self.shoot(1.0));
+                    if self.burst_remaining == 0 {
+                        self.cooldown = self.burst_timer + self.scaled_cooldown();
+                    } else {
+                        self.burst_timer += interval.max(MIN_SHOT_INTERVAL);
+                    }
+                }
+            }
+            FireMode::Charge { min_seconds, max_seconds, max_power } => {
+                if self.trigger {
+                    if self.charge.is_none() && self.cooldown <= 0.0 && self.ready() {
+                        self.charge = Some(0.0);
+                    }
+                    if let Some(charge) = self.charge.as_mut() {
+                        *charge = (*charge + dt).min(max_seconds);
+                    }
+                } else if let Some(charge) = self.charge.take() {
+                    if charge >= min_seconds && self.ready() {
+                        let t = if max_seconds > min_seconds { (charge - min_seconds) / (max_seconds - min_seconds) } else { 1.0 };
+                        shots.push(self.shoot(1.0 + (max_power - 1.0) * t));
+                        self.cooldown = self.scaled_cooldown();
+                    }
+                }
+            }
+        }
+        self.cooldown = self.cooldown.max(0.0);
+        shots
+    }
+}
+
+// 可以切换的一组武器。当前使用的武器放在实体的 Weapon 组件中，其它武器保存在这里，
+// 切换回来时保持原来的弹药、热量和冷却
+#[derive(Component, Debug)]
+pub struct WeaponLoadout {
+    slots: Vec<Option<Weapon>>,
+    selected: usize,
+}
+
+impl WeaponLoadout {
+    // 返回武器组和作为当前武器的第一件武器，definitions 不能为空
+    pub fn new(definitions: impl IntoIterator<Item = WeaponDefinition>) -> (Self, Weapon) {
+        let mut slots: Vec<Option<Weapon>> = definitions.into_iter().map(|definition| Some(Weapon::new(definition))).collect();
+        let first = slots
+            .first_mut()
+            .and_then(Option::take)
+            .expect("a weapon loadout needs at least one weapon");
+        (Self { slots, selected: 0 }, first)
+    }
+
+    pub fn weapon_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    // 按 step 前后循环切换武器
+    pub fn cycle(&mut self, step: isize, active: &mut Weapon) -> bool {
+        let len = self.slots.len() as isize;
+        let index = (self.selected as isize + step).rem_euclid(len) as usize;
+        self.select(index, active)
+    }
+
+    // 切换到第 index 件武器，active 是实体当前的 Weapon 组件。index 无效或已经选中时返回 false
+    pub fn select(&mut self, index: usize, active: &mut Weapon) -> bool {
+        if index == self.selected {
+            return false;
+        }
+        let Some(next) = self.slots.get_mut(index).and_then(Option::take) else {
+            return false;
+        };
+        let mut previous = std::mem::replace(active, next);
+        previous.holster();
+        self.slots[self.selected] = Some(previous);
+        self.selected = index;
+        true
+    }
+}
+
+// 制导：以不超过 turn_rate（弧度/秒）的速率把速度方向转向最近的小行星，速率不变
+#[derive(Component, Debug)]
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
+// 一道激光，附加在激光的显示实体上。射出时只记下起点、方向和射程，显示实体先隐藏；
+// 下一个固定步长中再做射线检测，并在同一步长内对命中的目标造成 damage 点伤害，伤害来源是 source。
+// 每道激光只结算一次
+#[derive(Component, Debug)]
+pub struct LaserHit {
+    pub source: Entity,
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub range: f32,
+    pub damage: f32,
+    resolved: bool,
+}
+
+impl LaserHit {
+    pub fn new(source: Entity, origin: Vec3, direction: Vec3, range: f32, damage: f32) -> Self {
+        Self { source, origin, direction, range, damage, resolved: false }
+    }
+}
+
+// 武器射出一发时发送。entity 是带有 Weapon 组件的实体，power 是威力系数（蓄力武器大于 1）
+#[derive(Event, Debug)]
+pub struct WeaponFired {
+    pub entity: Entity,
+    pub power: f32,
+}
+
+pub struct WeaponPlugin;
+
+// 控制系统应该在 update_weapons 之前设置扳机，生成弹丸的系统在它之后读取 WeaponFired。
+// 制导在积分加速度阶段转向；激光在固定步长中、伤害结算之前检测命中并发出伤害，不会因为这一帧没有运行固定步长而丢失
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WeaponFired>()
+            .add_systems(Update, update_weapons)
+            .add_systems(FixedUpdate, (
+                steer_homing_missiles.in_set(PhysicsSet::IntegrateAcceleration),
+                resolve_laser_hits.in_set(PhysicsSet::RespondCollisions).before(HealthSet::Damage),
+            ));
+    }
+}
+
+pub fn update_weapons(mut query: Query<(Entity, &mut Weapon)>, mut fired_events: EventWriter<WeaponFired>, time: Res<Time>) {
+    let dt = time.delta_seconds();
+    for (entity, mut weapon) in query.iter_mut() {
+        for power in weapon.update(dt) {
+            fired_events.send(WeaponFired { entity, power });
+        }
+    }
+}
+
+fn steer_homing_missiles(
+    mut missiles: Query<(&Transform, &mut Velocity, &Homing)>,
+    targets: Query<&Transform, With<Asteroid>>,
+    arena: Res<Arena>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (transform, mut velocity, homing) in missiles.iter_mut() {
+        let Some(to_target) = targets
+            .iter()
+            .map(|target| arena.offset(transform.translation, target.translation))
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        else {
+            continue;
+        };
+        let (Some(heading), Some(desired)) = (velocity.value.try_normalize(), to_target.try_normalize()) else {
+            continue;
+        };
+        let max_turn = homing.turn_rate * dt;
+        let new_heading = if heading.angle_between(desired) <= max_turn {
+            desired
+        } else {
+            let axis = heading.cross(desired).try_normalize().unwrap_or(Vec3::Y);
+            Quat::from_axis_angle(axis, max_turn) * heading
+        };
+        velocity.value = new_heading * velocity.value.length();
+    }
+}
+
+// 射线检测和伤害在同一个固定步长内完成，命中的小行星不会在两者之间被回收、换成另一颗。
+// 激光只由飞船发射，所以和飞船的弹丸一样只命中小行星和敌人。
+// 结算后把光束沿射线方向伸到命中点（没有命中时伸到射程）并显示出来
+fn resolve_laser_hits(
+    mut beams: Query<(&mut LaserHit, &mut Transform, &mut Visibility)>,
+    colliders: Query<(Entity, &Transform, &Collider), Without<LaserHit>>,
+    mut damage_events: EventWriter<DamageDealt>,
+    arena: Res<Arena>,
+) {
+    let probe = Collider::default()
+        .with_layers(CollisionLayers::PLAYER_PROJECTILE, CollisionLayers::ASTEROID | CollisionLayers::ENEMY);
+    for (mut hit, mut transform, mut visibility) in beams.iter_mut() {
+        if hit.resolved {
+            continue;
+        }
+        hit.resolved = true;
+        let target = raycast(hit.origin, hit.direction, hit.range, &probe, colliders.iter(), &arena);
+        if let Some((target, _)) = target {
+            damage_events.send(DamageDealt { target, source: Some(hit.source), amount: hit.damage });
+        }
+        let length = target.map_or(hit.range, |(_, distance)| distance);
+        transform.translation = hit.origin + hit.direction * length * 0.5;
+        transform.scale.z = length;
+        *visibility = Visibility::Visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 64 Hz，和固定步长相同，所有时间都是 2 的幂的倍数，累加没有舍入误差
+    const DT: f32 = 1.0 / 64.0;
+
+    fn weapon(mode: FireMode, ammo: Ammo, cooldown_seconds: f32) -> Weapon {
+        Weapon::new(WeaponDefinition {
+            name: "test".to_string(),
+            kind: WeaponKind::Missile,
+            mode,
+            ammo,
+            cooldown_seconds,
+            projectile_speed: 1.0,
+            damage: 1.0,
+        })
+    }
+
+    // 推进 seconds 秒，返回射出的每一发的威力
+    fn run(weapon: &mut Weapon, seconds: f32, dt: f32) -> Vec<f32> {
+        let steps = (seconds / dt).round() as u32;
+        (0..steps).flat_map(|_| weapon.update(dt)).collect()
+    }
+
+    #[test]
+    fn auto_fire_rate_does_not_depend_on_frame_rate() {
+        for dt in [DT, 3.0 / 16.0] {
+            let mut auto = weapon(FireMode::Auto, Ammo::Unlimited, 0.25);
+            auto.trigger = true;
+            // 9 秒内应该射出 9 / 0.25 = 36 发，起止时刻的取舍最多差一发
+            let shots = run(&mut auto, 9.0, dt).len();
+            assert!(shots.abs_diff(36) <= 1, "dt = {dt}: {shots} shots");
+        }
+    }
+
+    #[test]
+    fn released_trigger_does_not_bank_shots() {
+        let mut auto = weapon(FireMode::Auto, Ammo::Unlimited, 0.25);
+        run(&mut auto, 2.0, DT);
+        auto.trigger = true;
+        assert_eq!(auto.update(DT).len(), 1);
+    }
+
+    #[test]
+    fn burst_fires_count_shots_per_trigger_pull() {
+        let mut burst = weapon(FireMode::Burst { count: 3, interval: 0.0625 }, Ammo::Unlimited, 1.0);
+        burst.trigger = true;
+        assert_eq!(burst.update(DT).len(), 1);
+        burst.trigger = false;
+        assert_eq!(run(&mut burst, 2.0, DT).len(), 2);
+
+        // 按住扳机时每轮连发之间间隔 cooldown_seconds
+        burst.trigger = true;
+        assert_eq!(run(&mut burst, 1.5, DT).len(), 6);
+    }
+
+    #[test]
+    fn charge_fires_on_release_after_threshold() {
+        let mode = FireMode::Charge { min_seconds: 0.5, max_seconds: 1.0, max_power: 3.0 };
+        let mut charge = weapon(mode, Ammo::Unlimited, 0.0);
+
+        charge.trigger = true;
+        assert!(run(&mut charge, 0.25, DT).is_empty());
+        charge.trigger = false;
+        assert!(charge.update(DT).is_empty());
+
+        charge.trigger = true;
+        assert!(run(&mut charge, 0.75, DT).is_empty());
+        charge.trigger = false;
+        assert_eq!(charge.update(DT), vec![2.0]);
+    }
+
+    #[test]
+    fn overheated_weapon_recovers_after_cooling() {
+        let ammo = Ammo::Heat { per_shot: 0.6, cooling: 0.5, recover: 0.25 };
+        let mut heat = weapon(FireMode::Auto, ammo, 0.0625);
+        heat.trigger = true;
+
+        assert_eq!(run(&mut heat, 0.125, DT).len(), 2);
+        assert!(heat.overheated);
+        // 热量从接近 1.2 降到 0.25 需要大约 1.9 秒
+        assert!(run(&mut heat, 1.5, DT).is_empty());
+        assert!(!run(&mut heat, 1.0, DT).is_empty());
+    }
+}
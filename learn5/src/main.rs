@@ -3,14 +3,33 @@ mod components;
 use bevy::prelude::*;
 use components::asset_loading::AssetLoaderPlugin;
 use components::asteroids::AsteroidPlugin;
+use components::asteroid_mesh::AsteroidMeshPlugin;
 use components::collision_detection::CollisionDetectionPlugin;
 use components::despawn::DespawnPlugin;
 use components::spaceship::SpaceshipPlugin;
 use components::movement::MovementPlugin;
-use components::debug::DebugPlugin;
 use components::camera::CameraPlugin;
+use components::schedule::SchedulePlugin;
+use components::arena::ArenaPlugin;
+use components::waves::WavePlugin;
+use components::rng::GameRngPlugin;
+use components::health::HealthPlugin;
+use components::archetypes::ArchetypePlugin;
+use components::score::ScorePlugin;
+use components::pickups::PickupPlugin;
+use components::gravity::GravityPlugin;
+use components::weapons::WeaponPlugin;
+use components::player::PlayerPlugin;
+use components::input_map::InputMapPlugin;
+use components::ships::ShipPlugin;
 
 fn main() {
+    // cargo run -- --bench-collisions 3000
+    if let Some(count) = components::benchmark::collision_benchmark_arg() {
+        components::benchmark::run_collision_benchmark(count);
+        return;
+    }
+
     App::new()
     // build-ins
     .insert_resource(ClearColor(Color::rgb(1.0, 1.0, 1.0)))
@@ -20,13 +39,31 @@ fn main() {
     })
     .add_plugins(DefaultPlugins)
     // 用户插件
-    .add_plugins(AssetLoaderPlugin)
+    .add_plugins(SchedulePlugin)
+    // cargo run -- --seed 42
+    .add_plugins(GameRngPlugin { seed: components::rng::seed_arg() })
+    .add_plugins(ArenaPlugin)
+    // cargo run -- --collider-fit spaceship=capsule
+    .add_plugins(AssetLoaderPlugin { fits: components::asset_loading::collider_fit_args() })
     .add_plugins(MovementPlugin)
-    //.add_plugins(DebugPlugin)
+    .add_plugins(GravityPlugin)
+    // cargo run -- --debug
+    .add_plugins(components::debug::DebugPlugin { enabled: components::debug::debug_arg() })
+    .add_plugins(InputMapPlugin)
+    .add_plugins(WeaponPlugin)
+    .add_plugins(ShipPlugin)
     .add_plugins(SpaceshipPlugin)
+    .add_plugins(PlayerPlugin)
     .add_plugins(AsteroidPlugin)
+    // cargo run -- --asteroid-model gltf
+    .add_plugins(AsteroidMeshPlugin { model: components::asteroid_mesh::asteroid_model_arg().unwrap_or_default() })
+    .add_plugins(ArchetypePlugin)
+    .add_plugins(WavePlugin)
     .add_plugins(CameraPlugin)
     .add_plugins(CollisionDetectionPlugin)
+    .add_plugins(HealthPlugin)
+    .add_plugins(ScorePlugin)
+    .add_plugins(PickupPlugin)
     .add_plugins(DespawnPlugin)
     .run();
 }